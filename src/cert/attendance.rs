@@ -0,0 +1,289 @@
+use serde::Deserialize;
+use std::error::Error;
+
+use super::{
+    csv_data::{Attendee, IdKind, Identification, ParseError, Role, Workload},
+    name::{self, NameCase},
+};
+
+/// Minimum share of the event an attendee must attend to get a certificate.
+pub const DEFAULT_MIN_ATTENDANCE: u32 = 75;
+
+/// A session of the event.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Session {
+    #[serde(rename = "SESSAO")]
+    pub name: String,
+    #[serde(rename = "DURACAO")]
+    pub duration: Workload,
+}
+
+/// An attendee listed in the presence sheets.
+#[derive(Debug, Clone, PartialEq)]
+struct Person {
+    name: String,
+    id: Identification,
+    role: Role,
+    /// Sessions attended, by session index.
+    present: Vec<bool>,
+}
+
+/// An attendee without enough attendance for a certificate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ineligible {
+    pub name: String,
+    pub id: Identification,
+    pub attended: Workload,
+    /// Percentage of the event attended.
+    pub percent: u32,
+}
+
+/// Presence of the attendees in each session of an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attendance {
+    sessions: Vec<Session>,
+    people: Vec<Person>,
+}
+
+/// Whether a presence mark means the attendee was there.
+fn present(mark: &str) -> Result<bool, ParseError<String>> {
+    match mark.trim().to_lowercase().as_str() {
+        "" | "0" | "f" | "n" | "falta" | "não" | "nao" | "ausente" => Ok(false),
+        "1" | "x" | "p" | "s" | "sim" | "presente" => Ok(true),
+        _ => Err(ParseError::new(
+            "Presence mark like x or empty",
+            mark.to_owned(),
+        )),
+    }
+}
+
+impl Attendance {
+    pub fn new(sessions: Vec<Session>) -> Self {
+        Self {
+            sessions,
+            people: Vec::new(),
+        }
+    }
+
+    /// Read the sessions from a CSV with the `SESSAO` and `DURACAO` columns.
+    pub fn read_sessions<R>(src: R) -> csv::Result<Self>
+    where
+        R: std::io::Read,
+    {
+        let sessions = csv::Reader::from_reader(src)
+            .deserialize()
+            .collect::<csv::Result<Vec<_>>>()?;
+        Ok(Self::new(sessions))
+    }
+
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    fn session(&self, name: &str) -> Result<usize, ParseError<String>> {
+        self.sessions
+            .iter()
+            .position(|session| session.name == name.trim())
+            .ok_or_else(|| ParseError::new("Known session", name.to_owned()))
+    }
+
+    /// Read a presence sheet with the attendee columns of the attendees
+    /// sheet, except `CH`, and one column of presence marks per session.
+    pub fn read_sheet<R>(&mut self, src: R) -> Result<(), Box<dyn Error>>
+    where
+        R: std::io::Read,
+    {
+        let mut rdr = csv::Reader::from_reader(src);
+        let headers = rdr.headers()?.clone();
+        let mut sessions = Vec::new();
+        for (col, header) in headers.iter().enumerate() {
+            if !matches!(
+                header,
+                "NOME" | "CPF" | "IDENTIFICACAO" | "TIPO_ID" | "FUNCAO"
+            ) {
+                sessions.push((col, self.session(header)?));
+            }
+        }
+        for record in rdr.records() {
+            let record = record?;
+            let person = self.person(&headers, &record)?;
+            for &(col, session) in &sessions {
+                if present(record.get(col).unwrap_or_default())? {
+                    self.people[person].present[session] = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the sheet of a single session listing the attendees present.
+    pub fn read_session<R>(&mut self, session: &str, src: R) -> Result<(), Box<dyn Error>>
+    where
+        R: std::io::Read,
+    {
+        let session = self.session(session)?;
+        let mut rdr = csv::Reader::from_reader(src);
+        let headers = rdr.headers()?.clone();
+        for record in rdr.records() {
+            let person = self.person(&headers, &record?)?;
+            self.people[person].present[session] = true;
+        }
+        Ok(())
+    }
+
+    /// Index of the person of a sheet row, added if not seen yet.
+    fn person(
+        &mut self,
+        headers: &csv::StringRecord,
+        record: &csv::StringRecord,
+    ) -> Result<usize, Box<dyn Error>> {
+        let field = |names: &[&str]| {
+            headers
+                .iter()
+                .position(|col| names.contains(&col))
+                .and_then(|col| record.get(col))
+                .unwrap_or_default()
+                .to_owned()
+        };
+        let kind = IdKind::try_from(field(&["TIPO_ID"]))?;
+        let id = Identification::new(kind, field(&["CPF", "IDENTIFICACAO"]))?;
+        if let Some(i) = self.people.iter().position(|person| person.id == id) {
+            return Ok(i);
+        }
+
+        let raw_name = field(&["NOME"]);
+        if raw_name.trim().is_empty() {
+            return Err(ParseError::new("Non-empty attendee name", raw_name).into());
+        }
+        self.people.push(Person {
+            name: name::normalize(&raw_name, NameCase::Normalize),
+            id,
+            role: Role::try_from(field(&["FUNCAO"]))?,
+            present: vec![false; self.sessions.len()],
+        });
+        Ok(self.people.len() - 1)
+    }
+
+    /// Total duration of the event.
+    pub fn total(&self) -> Workload {
+        Workload::from_minutes(self.sessions.iter().map(|s| s.duration.minutes()).sum())
+    }
+
+    /// Split the attendees between the ones that attended at least
+    /// `min_percent` of the event, with their attended time as workload, and
+    /// the ineligible ones, which include the ones that attended no session.
+    pub fn attendees(&self, min_percent: u32) -> (Vec<Attendee>, Vec<Ineligible>) {
+        let total = self.total().minutes();
+        let mut eligible = Vec::new();
+        let mut ineligible = Vec::new();
+        for person in &self.people {
+            let attended = self
+                .sessions
+                .iter()
+                .zip(&person.present)
+                .filter(|(_, present)| **present)
+                .map(|(session, _)| session.duration.minutes())
+                .sum::<u32>();
+            if attended > 0 && attended * 100 >= total * min_percent {
+                eligible.push(Attendee {
+                    name: person.name.clone(),
+                    id: person.id.clone(),
+                    workload: Workload::from_minutes(attended),
+                    role: person.role,
+                });
+            } else {
+                ineligible.push(Ineligible {
+                    name: person.name.clone(),
+                    id: person.id.clone(),
+                    attended: Workload::from_minutes(attended),
+                    percent: attended * 100 / total.max(1),
+                });
+            }
+        }
+        (eligible, ineligible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSIONS: &str = "SESSAO,DURACAO\n04/05,2h\n11/05,2h\n18/05,1h30\n25/05,30min\n";
+
+    #[test]
+    fn presence_sheet() {
+        let mut attendance =
+            Attendance::read_sessions(SESSIONS.as_bytes()).expect("valid sessions");
+        let sheet = "NOME,CPF,FUNCAO,04/05,11/05,18/05,25/05\n\
+                     ana silva,762.050.858-95,,x,x,x,\n\
+                     Bia,754.751.875-33,monitor,x,,x,x\n";
+        attendance
+            .read_sheet(sheet.as_bytes())
+            .expect("valid presence sheet");
+
+        assert_eq!(Workload::from_hours(6), attendance.total());
+        let (eligible, ineligible) = attendance.attendees(DEFAULT_MIN_ATTENDANCE);
+        assert_eq!(1, eligible.len());
+        assert_eq!("Ana Silva", eligible[0].name);
+        assert_eq!(Workload::from_minutes(330), eligible[0].workload);
+        assert_eq!(Role::Participant, eligible[0].role);
+        assert_eq!(
+            vec![Ineligible {
+                name: "Bia".to_owned(),
+                id: Identification::new(IdKind::Cpf, "754.751.875-33".to_owned())
+                    .expect("valid cpf"),
+                attended: Workload::from_hours(4),
+                percent: 66,
+            }],
+            ineligible
+        );
+
+        let (eligible, ineligible) = attendance.attendees(60);
+        assert_eq!(2, eligible.len());
+        assert_eq!(Role::Monitor, eligible[1].role);
+        assert!(ineligible.is_empty());
+
+        let absent = "NOME,CPF,04/05,11/05,18/05,25/05
+Caio,647.748.630-09,,,,
+";
+        attendance
+            .read_sheet(absent.as_bytes())
+            .expect("valid presence sheet");
+        let (eligible, ineligible) = attendance.attendees(0);
+        assert_eq!(2, eligible.len());
+        assert_eq!("Caio", ineligible[0].name);
+        assert_eq!(0, ineligible[0].percent);
+    }
+
+    #[test]
+    fn session_sheets() {
+        let mut attendance =
+            Attendance::read_sessions(SESSIONS.as_bytes()).expect("valid sessions");
+        for session in ["04/05", "11/05", "18/05"] {
+            let sheet = "NOME,CPF\nAna,76205085895\n";
+            attendance
+                .read_session(session, sheet.as_bytes())
+                .expect("valid session sheet");
+        }
+        let (eligible, _) = attendance.attendees(DEFAULT_MIN_ATTENDANCE);
+        assert_eq!(1, eligible.len());
+        assert_eq!("762.050.858-95", eligible[0].id.as_str());
+        assert_eq!(Workload::from_minutes(330), eligible[0].workload);
+
+        assert!(attendance
+            .read_session("01/06", "NOME,CPF\n".as_bytes())
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_sheets() {
+        let mut attendance =
+            Attendance::read_sessions(SESSIONS.as_bytes()).expect("valid sessions");
+        let unknown_session = "NOME,CPF,01/06\nAna,762.050.858-95,x\n";
+        assert!(attendance.read_sheet(unknown_session.as_bytes()).is_err());
+        let invalid_mark = "NOME,CPF,04/05\nAna,762.050.858-95,talvez\n";
+        assert!(attendance.read_sheet(invalid_mark.as_bytes()).is_err());
+        let invalid_cpf = "NOME,CPF,04/05\nAna,762.050.858-94,x\n";
+        assert!(attendance.read_sheet(invalid_cpf.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,25 @@
+{% if event.id is not none %}
+SET @evid := (SELECT {{schema.evento.id}} FROM {{schema.evento}} WHERE {{schema.evento.id}}={{event.id}} UNION ALL SELECT NULL FROM (SELECT 1 UNION ALL SELECT 2) AS missing WHERE NOT EXISTS (SELECT {{schema.evento.id}} FROM {{schema.evento}} WHERE {{schema.evento.id}}={{event.id}}));
+{% else %}
+INSERT IGNORE INTO {{schema.evento}} ({{schema.evento.nome}}, {{schema.evento.data}}, {{schema.evento.img}}) VALUES ({{event.name|quote}}, {{event.date|quote}}, {{img|quote}});
+SET @evid := (SELECT {{schema.evento.id}} FROM {{schema.evento}} WHERE {{schema.evento.nome}}={{event.name|quote}} AND {{schema.evento.data}}={{event.date|quote}} AND {{schema.evento.img}}={{img|quote}});
+{% endif %}
+INSERT IGNORE INTO {{schema.usuario}} ({{schema.usuario.nome}}, {{schema.usuario.identificacao}}) VALUES {% for att in attendees %}{% if not loop.first %},{% endif %}({{att.name|quote}}, {{att.id|quote}}){% endfor %};
+{% for txt in texts %}
+{% if txt.id is not none %}
+SET {{txt.var}} = {{txt.id}};
+{% else %}
+INSERT IGNORE INTO {{schema.texto}} ({{schema.texto.texto}}) VALUES ({{txt.text|quote}});
+SET {{txt.var}} := (SELECT {{schema.texto.id}} FROM {{schema.texto}} WHERE {{schema.texto.texto}}={{txt.text|quote}});
+{% endif %}
+{% endfor %}
+{% for att in attendees %}
+SET @uid{{loop.index0}} := (SELECT {{schema.usuario.id}} FROM {{schema.usuario}} WHERE {{schema.usuario.identificacao}}={{att.id|quote}});
+{% endfor %}
+{% if event.id is not none %}
+{% for att in attendees %}
+INSERT INTO {{schema.participacao}} ({{schema.participacao.usuario}}, {{schema.participacao.evento}}, {{schema.participacao.texto}}, {{schema.participacao.ch}}{% if codes %}, {{schema.participacao.codigo}}{% endif %}) SELECT @uid{{loop.index0}}, @evid, {{att.text}}, {{att.workload}}{% if codes %}, {{att.code|quote}}{% endif %} FROM DUAL WHERE NOT EXISTS (SELECT {{schema.participacao.usuario}} FROM {{schema.participacao}} WHERE {{schema.participacao.usuario}}=@uid{{loop.index0}} AND {{schema.participacao.evento}}=@evid);
+{% endfor %}
+{% else %}
+INSERT INTO {{schema.participacao}} ({{schema.participacao.usuario}}, {{schema.participacao.evento}}, {{schema.participacao.texto}}, {{schema.participacao.ch}}{% if codes %}, {{schema.participacao.codigo}}{% endif %}) VALUES {% for att in attendees %}{% if not loop.first %},{% endif %}(@uid{{loop.index0}}, @evid, {{att.text}}, {{att.workload}}{% if codes %}, {{att.code|quote}}{% endif %}){% endfor %};
+{% endif %}
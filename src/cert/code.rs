@@ -0,0 +1,222 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::str::FromStr;
+
+use super::{
+    csv_data::{Attendee, EventData, ParseError},
+    ledger::Issued,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Number of HMAC bytes kept in a verification code.
+const CODE_BYTES: usize = 10;
+
+/// Characters of a verification code between each `-`.
+const GROUP_LEN: usize = 4;
+
+/// A code that identifies a single participation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Code(String);
+
+impl Code {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let code = data_encoding::BASE32_NOPAD
+            .encode(bytes)
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(GROUP_LEN)
+            .map(|group| group.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("-");
+        Self(code)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let code = self.0.replace('-', "");
+        data_encoding::BASE32_NOPAD
+            .decode(code.as_bytes())
+            .expect("codes are always valid base32")
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Code {
+    type Err = ParseError<String>;
+
+    /// Parse a code ignoring case, spaces and hyphens.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect::<String>()
+            .to_uppercase();
+        match data_encoding::BASE32_NOPAD.decode(code.as_bytes()) {
+            Ok(bytes) if bytes.len() == CODE_BYTES => Ok(Self::from_bytes(&bytes)),
+            _ => Err(ParseError::new("Valid verification code", s.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for Code {
+    type Error = ParseError<String>;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Code> for String {
+    fn from(code: Code) -> Self {
+        code.0
+    }
+}
+
+impl std::fmt::Display for Code {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Generates verification codes from a secret key.
+///
+/// A code is an HMAC of the event and the attendee identification, so the
+/// same participation always gets the same code, even after its workload is
+/// corrected, and no one can forge a code without the key.
+#[derive(Clone, PartialEq)]
+pub struct Signer {
+    key: Vec<u8>,
+}
+
+impl std::fmt::Debug for Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Signer").finish_non_exhaustive()
+    }
+}
+
+impl Signer {
+    pub fn new(key: &[u8]) -> Self {
+        Self { key: key.to_vec() }
+    }
+
+    fn mac(&self, event: &str, date: &str, id: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any size");
+        let msg = [event, date, id].join("\u{1f}");
+        mac.update(msg.as_bytes());
+        mac
+    }
+
+    /// Verification code of `att` participation in the event.
+    pub fn code(&self, data: &EventData, att: &Attendee) -> Code {
+        let hash = self
+            .mac(&data.name, &data.date.to_string(), att.id.as_str())
+            .finalize()
+            .into_bytes();
+        Code::from_bytes(&hash[..CODE_BYTES])
+    }
+
+    /// Check whether the code of an issued certificate was generated with
+    /// this key for the same event and attendee.
+    pub fn check(&self, issued: &Issued) -> bool {
+        let mac = self.mac(&issued.event, &issued.date, &issued.cpf);
+        mac.verify_truncated_left(&issued.code.to_bytes()).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::csv_data::{Cpf, EventDate, EventDesc, Role, Workload};
+    use std::collections::BTreeMap;
+    use time::macros::date;
+
+    fn participation(workload: u32) -> (EventData, Attendee) {
+        let data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Id(1),
+            date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::new(),
+            max_workload: None,
+        };
+        let att = Attendee {
+            name: "A".to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(workload),
+            role: Role::Participant,
+        };
+        (data, att)
+    }
+
+    #[test]
+    fn code_format() {
+        let (data, att) = participation(1);
+        let code = Signer::new(b"secret").code(&data, &att);
+        let groups = code.as_str().split('-').collect::<Vec<_>>();
+
+        assert_eq!(4, groups.len());
+        assert!(groups.iter().all(|group| group.len() == GROUP_LEN));
+        assert!(code
+            .as_str()
+            .chars()
+            .all(|c| c == '-' || c.is_ascii_uppercase() || ('2'..='7').contains(&c)));
+    }
+
+    #[test]
+    fn deterministic_codes() {
+        let signer = Signer::new(b"secret");
+        let (data, att) = participation(1);
+        assert_eq!(signer.code(&data, &att), signer.code(&data, &att));
+
+        // corrected workloads keep the code
+        let (other_data, other_att) = participation(2);
+        assert_eq!(
+            signer.code(&data, &att),
+            signer.code(&other_data, &other_att)
+        );
+        let mut other_data = other_data;
+        other_data.name = "Other event".to_owned();
+        assert_ne!(
+            signer.code(&data, &att),
+            signer.code(&other_data, &other_att)
+        );
+        assert_ne!(
+            signer.code(&data, &att),
+            Signer::new(b"other secret").code(&data, &att)
+        );
+    }
+
+    #[test]
+    fn parse_code() {
+        let (data, att) = participation(1);
+        let code = Signer::new(b"secret").code(&data, &att);
+
+        let typed = code.as_str().replace('-', " ").to_lowercase();
+        assert_eq!(Ok(code.clone()), typed.parse::<Code>());
+        assert_eq!(Ok(code.clone()), code.as_str().parse::<Code>());
+        assert!("ABCD-EFGH".parse::<Code>().is_err());
+        assert!("ABCD-EFGH-IJKL-MNO1".parse::<Code>().is_err());
+    }
+
+    #[test]
+    fn check_issued() {
+        let signer = Signer::new(b"secret");
+        let (data, att) = participation(1);
+        let event = data.into_event(vec![att]).sign(signer.clone());
+        let mut issued = event.issued().remove(0);
+
+        assert!(signer.check(&issued));
+        assert!(!Signer::new(b"other secret").check(&issued));
+        issued.workload = Workload::from_hours(2);
+        assert!(signer.check(&issued));
+        issued.cpf = "647.748.630-09".to_owned();
+        assert!(!signer.check(&issued));
+    }
+}
@@ -0,0 +1,231 @@
+use serde::Deserialize;
+
+use super::csv_data::{IdKind, Identification, ParseError, Workload};
+use crate::sql::{quote, QueryPool, Schema, ToSQL};
+
+/// A value of an issued certificate to change, from the value it is expected
+/// to hold to the new one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Name {
+        from: String,
+        to: String,
+    },
+    Workload {
+        from: Workload,
+        to: Workload,
+    },
+    /// Certificate text, by text id.
+    Text {
+        from: u32,
+        to: u32,
+    },
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name { from, to } => write!(f, "name {from} -> {to}"),
+            Self::Workload { from, to } => write!(f, "workload {} -> {}", from.hm(), to.hm()),
+            Self::Text { from, to } => write!(f, "text {from} -> {to}"),
+        }
+    }
+}
+
+/// Changes to the certificate of an attendee in an event.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "CorrectionRecord")]
+pub struct Correction {
+    pub id: Identification,
+    /// Id of the event in the database.
+    pub event: u32,
+    pub changes: Vec<Change>,
+}
+
+/// A row of the corrections sheet.
+#[derive(Deserialize)]
+struct CorrectionRecord {
+    #[serde(rename = "CPF", alias = "IDENTIFICACAO")]
+    id: String,
+    #[serde(rename = "TIPO_ID", default)]
+    kind: String,
+    #[serde(rename = "EVENTO")]
+    event: u32,
+    #[serde(rename = "NOME_ATUAL", default)]
+    old_name: Option<String>,
+    #[serde(rename = "NOME_NOVO", default)]
+    new_name: Option<String>,
+    #[serde(rename = "CH_ATUAL", default)]
+    old_workload: Option<Workload>,
+    #[serde(rename = "CH_NOVA", default)]
+    new_workload: Option<Workload>,
+    #[serde(rename = "TEXTO_ATUAL", default)]
+    old_text: Option<u32>,
+    #[serde(rename = "TEXTO_NOVO", default)]
+    new_text: Option<u32>,
+}
+
+/// Pair an old and a new value, which must be given together.
+fn pair<T>(
+    expected: &'static str,
+    old: Option<T>,
+    new: Option<T>,
+) -> Result<Option<(T, T)>, ParseError<String>> {
+    match (old, new) {
+        (Some(old), Some(new)) => Ok(Some((old, new))),
+        (None, None) => Ok(None),
+        _ => Err(ParseError::new(expected, "only one of them".to_owned())),
+    }
+}
+
+impl TryFrom<CorrectionRecord> for Correction {
+    type Error = ParseError<String>;
+
+    fn try_from(record: CorrectionRecord) -> Result<Self, Self::Error> {
+        let id = Identification::new(IdKind::try_from(record.kind)?, record.id)?;
+        let mut changes = Vec::new();
+        let names = (
+            record.old_name.filter(|name| !name.trim().is_empty()),
+            record.new_name.filter(|name| !name.trim().is_empty()),
+        );
+        if let Some((from, to)) = pair("Both NOME_ATUAL and NOME_NOVO", names.0, names.1)? {
+            changes.push(Change::Name {
+                from: from.trim().to_owned(),
+                to: to.trim().to_owned(),
+            });
+        }
+        let workloads = pair(
+            "Both CH_ATUAL and CH_NOVA",
+            record.old_workload,
+            record.new_workload,
+        )?;
+        if let Some((from, to)) = workloads {
+            changes.push(Change::Workload { from, to });
+        }
+        let texts = pair(
+            "Both TEXTO_ATUAL and TEXTO_NOVO",
+            record.old_text,
+            record.new_text,
+        )?;
+        if let Some((from, to)) = texts {
+            changes.push(Change::Text { from, to });
+        }
+
+        if changes.is_empty() {
+            let err = ParseError::new("At least one change", id.as_str().to_owned());
+            return Err(err);
+        }
+        Ok(Self {
+            id,
+            event: record.event,
+            changes,
+        })
+    }
+}
+
+/// Read a corrections sheet.
+pub fn read_corrections<R>(src: R) -> csv::Result<Vec<Correction>>
+where
+    R: std::io::Read,
+{
+    csv::Reader::from_reader(src).deserialize().collect()
+}
+
+impl ToSQL for Correction {
+    /// Update the rows that still hold the old values, leaving the ones
+    /// already corrected or changed by someone else untouched.
+    fn to_sql_with(&self, schema: &Schema) -> QueryPool {
+        let mut pool = QueryPool::new();
+        let id = quote(self.id.as_str());
+        let event = self.event;
+        let usuario = schema.table("usuario");
+        let [user_id, nome, identificacao] =
+            schema.columns("usuario", ["id", "nome", "identificacao"]);
+        let participacao = schema.table("participacao");
+        let [p_user, p_event, p_text, ch] =
+            schema.columns("participacao", ["usuario", "evento", "texto", "ch"]);
+        let user = format!("(SELECT {user_id} FROM {usuario} WHERE {identificacao}={id})");
+        for change in &self.changes {
+            pool.add(match change {
+                Change::Name { from, to } => format!(
+                    "UPDATE {usuario} SET {nome}={} \
+                    WHERE {identificacao}={id} AND {nome}={}",
+                    quote(to),
+                    quote(from)
+                ),
+                Change::Workload { from, to } => format!(
+                    "UPDATE {participacao} SET {ch}={to} \
+                    WHERE {p_user}={user} AND {p_event}={event} AND {ch}={from}"
+                ),
+                Change::Text { from, to } => format!(
+                    "UPDATE {participacao} SET {p_text}={to} \
+                    WHERE {p_user}={user} AND {p_event}={event} AND {p_text}={from}"
+                ),
+            });
+        }
+        pool
+    }
+}
+
+impl ToSQL for Vec<Correction> {
+    fn to_sql_with(&self, schema: &Schema) -> QueryPool {
+        let mut pool = QueryPool::new();
+        for correction in self {
+            pool.add_many(correction.to_sql_with(schema));
+        }
+        pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrections_to_sql() {
+        let sheet = "CPF,EVENTO,NOME_ATUAL,NOME_NOVO,CH_ATUAL,CH_NOVA,TEXTO_ATUAL,TEXTO_NOVO\n\
+                     75475187533,12,Ana Slva,Ana Silva,,,,\n\
+                     647.748.630-09,12,,,2,1h30,3,4\n";
+        let corrections = read_corrections(sheet.as_bytes()).expect("valid corrections");
+        let user = "(SELECT id FROM usuario WHERE identificacao='647.748.630-09')";
+        let result = [
+            "UPDATE usuario SET nome='Ana Silva' \
+            WHERE identificacao='754.751.875-33' AND nome='Ana Slva'"
+                .to_owned(),
+            format!("UPDATE participacao SET ch=1.5 WHERE usuario={user} AND evento=12 AND ch=2"),
+            format!(
+                "UPDATE participacao SET texto=4 WHERE usuario={user} AND evento=12 AND texto=3"
+            ),
+        ];
+        assert_eq!(
+            result.to_vec(),
+            corrections.to_sql().into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn quote_corrections() {
+        let sheet = "CPF,EVENTO,NOME_ATUAL,NOME_NOVO\n\
+                     75475187533,12,Ana D'avila,Ana d'Ávila\n";
+        let corrections = read_corrections(sheet.as_bytes()).expect("valid corrections");
+        assert_eq!(
+            "UPDATE usuario SET nome='Ana d''Ávila' \
+            WHERE identificacao='754.751.875-33' AND nome='Ana D''avila';\n",
+            corrections.to_sql().to_string()
+        );
+    }
+
+    #[test]
+    fn invalid_corrections() {
+        let header = "CPF,EVENTO,NOME_ATUAL,NOME_NOVO,CH_ATUAL,CH_NOVA\n";
+        for row in [
+            "754.751.875-33,12,Ana,,,",
+            "754.751.875-33,12,,,2,",
+            "754.751.875-33,12,,,,",
+            "754.751.875-34,12,Ana,Ana Silva,,",
+        ] {
+            let sheet = format!("{header}{row}\n");
+            assert!(read_corrections(sheet.as_bytes()).is_err(), "{row}");
+        }
+    }
+}
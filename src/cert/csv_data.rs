@@ -1,9 +1,15 @@
-use serde::{de, Deserialize};
+use serde::{de, Deserialize, Serialize};
+use std::collections::BTreeMap;
 use time::{format_description::FormatItem, macros::format_description, Date};
 
-use super::Event;
+use super::{
+    name::{self, NameCase},
+    text::{Placeholders, TextError},
+    Event,
+};
 
 const DATE_FMT: &[FormatItem<'_>] = format_description!("[day]/[month]/[year]");
+const ISO_DATE_FMT: &[FormatItem<'_>] = format_description!("[year]-[month]-[day]");
 
 macro_rules! deserialize_fn {
     ($f:ident(): $fn:expr, $from:ty => $b:ty) => {
@@ -41,22 +47,96 @@ impl<T: std::fmt::Display> std::fmt::Display for ParseError<T> {
 impl<T: std::fmt::Display + std::fmt::Debug> std::error::Error for ParseError<T> {}
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(from = "EventRecord")]
 pub struct EventData {
-    #[serde(rename = "NOME", deserialize_with = "parse_evt_name")]
+    /// Id of an event already in the database, to add attendees to it.
+    pub id: Option<u32>,
     pub name: String,
-    #[serde(rename = "DATA", deserialize_with = "parse_evt_date")]
     pub date: EventDate,
-    #[serde(rename = "TEXTO", deserialize_with = "parse_evt_desc")]
+    /// Text of participants and of roles without their own text.
     pub desc: EventDesc,
+    /// Texts of specific attendee roles.
+    pub roles: BTreeMap<Role, EventDesc>,
+    /// Largest workload an attendee may get.
+    pub max_workload: Option<Workload>,
+}
+
+/// A row of the event sheet.
+#[derive(Deserialize)]
+struct EventRecord {
+    #[serde(rename = "ID", default)]
+    id: Option<u32>,
+    #[serde(rename = "NOME", deserialize_with = "parse_evt_name")]
+    name: String,
+    #[serde(rename = "DATA", deserialize_with = "parse_evt_date")]
+    date: EventDate,
+    #[serde(rename = "TEXTO", deserialize_with = "parse_evt_desc")]
+    desc: EventDesc,
+    #[serde(
+        rename = "TEXTO_PALESTRANTE",
+        default,
+        deserialize_with = "parse_opt_evt_desc"
+    )]
+    speaker: Option<EventDesc>,
+    #[serde(
+        rename = "TEXTO_ORGANIZADOR",
+        default,
+        deserialize_with = "parse_opt_evt_desc"
+    )]
+    organizer: Option<EventDesc>,
+    #[serde(
+        rename = "TEXTO_MONITOR",
+        default,
+        deserialize_with = "parse_opt_evt_desc"
+    )]
+    monitor: Option<EventDesc>,
+    #[serde(rename = "CH_MAX", default)]
+    max_workload: Option<Workload>,
+}
+
+impl From<EventRecord> for EventData {
+    fn from(record: EventRecord) -> Self {
+        let roles = [
+            (Role::Speaker, record.speaker),
+            (Role::Organizer, record.organizer),
+            (Role::Monitor, record.monitor),
+        ]
+        .into_iter()
+        .filter_map(|(role, desc)| Some((role, desc?)))
+        .collect();
+
+        Self {
+            id: record.id,
+            name: record.name,
+            date: record.date,
+            desc: record.desc,
+            roles,
+            max_workload: record.max_workload,
+        }
+    }
 }
 
 impl EventData {
+    /// Text of the attendees with the given role.
+    pub fn desc(&self, role: Role) -> &EventDesc {
+        self.roles.get(&role).unwrap_or(&self.desc)
+    }
+
     pub fn into_event(self, attendees: Vec<Attendee>) -> Event {
         Event {
             data: self,
             atts: attendees,
+            signer: None,
         }
     }
+
+    /// Check the placeholders of every text of the event, which are only
+    /// checked for their syntax when the event file is read.
+    pub fn check_texts(&self, placeholders: &Placeholders) -> Result<(), TextError> {
+        std::iter::once(&self.desc)
+            .chain(self.roles.values())
+            .try_for_each(|desc| desc.check(placeholders))
+    }
 }
 
 fn validate_evt_name(name: String) -> Result<String, ParseError<String>> {
@@ -74,37 +154,286 @@ pub enum EventDesc {
     Text(String),
 }
 
-impl TryFrom<String> for EventDesc {
-    type Error = ParseError<String>;
+impl EventDesc {
+    /// Parse a description checking the text placeholders against `placeholders`.
+    pub fn parse(
+        value: String,
+        placeholders: &Placeholders,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let desc = Self::parse_unchecked(value)?;
+        desc.check(placeholders)?;
+        Ok(desc)
+    }
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
+    /// Parse a description whose placeholders are checked later.
+    fn parse_unchecked(value: String) -> Result<Self, Box<dyn std::error::Error>> {
         if value.is_empty() {
             let err = ParseError::new("Non-empty description Text or Id", value);
-            return Err(err);
+            return Err(err.into());
         }
         if let Ok(id) = value.parse::<u32>() {
             return Ok(EventDesc::Id(id));
         }
+        // the default placeholders must be closed whatever the others
+        Placeholders::default().balanced(&value)?;
         Ok(EventDesc::Text(value))
     }
+
+    /// Check the placeholders of a text, where catalog texts are checked
+    /// when the organization is read.
+    pub fn check(&self, placeholders: &Placeholders) -> Result<(), TextError> {
+        match self {
+            Self::Text(text) if catalog_name(text).is_none() => {
+                placeholders.validate(text).map(drop)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Name of a text of the organization catalog, written as `@name`.
+    pub fn catalog_name(&self) -> Option<&str> {
+        match self {
+            Self::Text(text) => catalog_name(text),
+            Self::Id(_) => None,
+        }
+    }
+}
+
+fn catalog_name(text: &str) -> Option<&str> {
+    text.strip_prefix('@')
+        .filter(|name| !name.is_empty() && !name.contains(char::is_whitespace))
+}
+
+impl TryFrom<String> for EventDesc {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(value, &Placeholders::default())
+    }
 }
-deserialize_fn!(parse_evt_desc(): EventDesc::try_from, String => EventDesc);
+deserialize_fn!(parse_evt_desc(): EventDesc::parse_unchecked, String => EventDesc);
+
+fn validate_opt_evt_desc(value: String) -> Result<Option<EventDesc>, Box<dyn std::error::Error>> {
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    EventDesc::parse_unchecked(value).map(Some)
+}
+deserialize_fn!(parse_opt_evt_desc(): validate_opt_evt_desc, String => Option<EventDesc>);
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum EventDate {
     Day(Date),
-    Period { start: Date, end: Date },
+    Period {
+        start: Date,
+        end: Date,
+    },
+    /// Non-contiguous days in chronological order.
+    Days(Vec<Date>),
+}
+
+/// How far in the future an event date may be.
+const MAX_DAYS_AHEAD: i64 = 366;
+
+const MONTHS: [&str; 12] = [
+    "janeiro",
+    "fevereiro",
+    "março",
+    "abril",
+    "maio",
+    "junho",
+    "julho",
+    "agosto",
+    "setembro",
+    "outubro",
+    "novembro",
+    "dezembro",
+];
+
+/// Portuguese enumeration like "a, b e c".
+fn enumerate<T: AsRef<str>>(items: &[T]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.as_ref().to_owned(),
+        [init @ .., last] => format!(
+            "{} e {}",
+            init.iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<_>>()
+                .join(", "),
+            last.as_ref()
+        ),
+    }
+}
+
+/// Parse a date as `dd/mm/yyyy`, `dd/mm/yy` or `yyyy-mm-dd`.
+///
+/// The day and month may have a single digit, as in `4/5/2023`. Two-digit
+/// years are taken as years of this century. Missing parts of
+/// `dd` or `dd/mm` dates are taken from `reference`, if any.
+fn parse_date(value: &str, reference: Option<Date>) -> Result<Date, Box<dyn std::error::Error>> {
+    let value = value.trim();
+    if let Ok(date) = Date::parse(value, ISO_DATE_FMT) {
+        return Ok(date);
+    }
+    let parts = value.split('/').map(str::trim).collect::<Vec<_>>();
+    let (day, month, year) = match (parts.as_slice(), reference) {
+        ([day], Some(date)) => (day.parse()?, date.month(), date.year()),
+        ([day, month], Some(date)) => (day.parse()?, month.parse::<u8>()?.try_into()?, date.year()),
+        ([day, month, year], _) if matches!(year.len(), 2 | 4) => (
+            day.parse()?,
+            month.parse::<u8>()?.try_into()?,
+            year.parse::<i32>()? + if year.len() == 2 { 2000 } else { 0 },
+        ),
+        _ => return Ok(Date::parse(value, DATE_FMT)?),
+    };
+    Ok(Date::from_calendar_date(year, month, day)?)
 }
 
-impl ToString for EventDate {
-    fn to_string(&self) -> String {
+impl EventDate {
+    /// Parse an event date rejecting dates over a year after `today`.
+    ///
+    /// Besides a single day, it accepts periods like "04/05/2023 - 06/05/2023"
+    /// or "04/05/2023 a 06/05/2023" and lists like "04, 11 e 18/05/2023",
+    /// where the days without month or year take them from the last one.
+    pub fn parse(value: &str, today: Date) -> Result<Self, Box<dyn std::error::Error>> {
+        let trimmed = value.trim();
+        let date = if trimmed.contains(',') || trimmed.contains(" e ") {
+            let items = trimmed
+                .split(',')
+                .flat_map(|item| item.split(" e "))
+                .map(str::trim)
+                .collect::<Vec<_>>();
+            let last = parse_date(items[items.len() - 1], None)?;
+            let mut days = items
+                .iter()
+                .map(|item| parse_date(item, Some(last)))
+                .collect::<Result<Vec<_>, _>>()?;
+            days.sort();
+            if days.windows(2).any(|pair| pair[0] == pair[1]) {
+                return Err(ParseError::new("Days without repetition", value.to_owned()).into());
+            }
+            Self::Days(days)
+        } else if let Some((start, end)) = Self::split_period(trimmed) {
+            let start = parse_date(start, None)?;
+            let end = parse_date(end, None)?;
+            if start >= end {
+                return Err(ParseError::new(
+                    "Start of the event happen before the end",
+                    value.to_owned(),
+                )
+                .into());
+            }
+            Self::Period { start, end }
+        } else {
+            Self::Day(parse_date(trimmed, None)?)
+        };
+
+        if date.last() - today > time::Duration::days(MAX_DAYS_AHEAD) {
+            let err = ParseError::new("Date at most a year from now", value.to_owned());
+            return Err(err.into());
+        }
+        Ok(date)
+    }
+
+    /// Split a period on " a ", " até " or on a dash that is not part of an
+    /// ISO date.
+    fn split_period(value: &str) -> Option<(&str, &str)> {
+        [" a ", " até ", " - "]
+            .into_iter()
+            .find_map(|sep| value.split_once(sep))
+            .or_else(|| {
+                if value.contains('/') {
+                    value.split_once('-')
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Last day of the event.
+    pub fn last(&self) -> Date {
+        match self {
+            Self::Day(date) | Self::Period { end: date, .. } => *date,
+            Self::Days(days) => *days.last().expect("event days are never empty"),
+        }
+    }
+
+    /// Number of days of the event.
+    pub fn days(&self) -> u32 {
         match self {
-            Self::Day(date) => format!("dia {}", date.format(DATE_FMT).unwrap()),
+            Self::Day(_) => 1,
+            Self::Period { start, end } => (*end - *start).whole_days() as u32 + 1,
+            Self::Days(days) => days.len() as u32,
+        }
+    }
+
+    /// Date in long Portuguese form, like "4 a 6 de maio de 2023".
+    pub fn long(&self) -> String {
+        let month = |date: &Date| MONTHS[u8::from(date.month()) as usize - 1];
+        match self {
+            Self::Day(date) => format!("{} de {} de {}", date.day(), month(date), date.year()),
+            Self::Period { start, end } if start.year() != end.year() => format!(
+                "{} de {} de {} a {} de {} de {}",
+                start.day(),
+                month(start),
+                start.year(),
+                end.day(),
+                month(end),
+                end.year()
+            ),
+            Self::Period { start, end } if start.month() != end.month() => format!(
+                "{} de {} a {} de {} de {}",
+                start.day(),
+                month(start),
+                end.day(),
+                month(end),
+                end.year()
+            ),
             Self::Period { start, end } => format!(
-                "período de {} a {}",
-                start.format(DATE_FMT).unwrap(),
-                end.format(DATE_FMT).unwrap()
+                "{} a {} de {} de {}",
+                start.day(),
+                end.day(),
+                month(end),
+                end.year()
             ),
+            Self::Days(days) => {
+                // days grouped by month and months grouped by year
+                let mut years: Vec<(i32, Vec<String>)> = Vec::new();
+                for group in days.chunk_by(|a, b| (a.year(), a.month()) == (b.year(), b.month())) {
+                    let names = group
+                        .iter()
+                        .map(|d| d.day().to_string())
+                        .collect::<Vec<_>>();
+                    let month = format!("{} de {}", enumerate(&names), month(&group[0]));
+                    match years.last_mut() {
+                        Some((year, months)) if *year == group[0].year() => months.push(month),
+                        _ => years.push((group[0].year(), vec![month])),
+                    }
+                }
+                let years = years
+                    .iter()
+                    .map(|(year, months)| format!("{} de {year}", enumerate(months)))
+                    .collect::<Vec<_>>();
+                enumerate(&years)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for EventDate {
+    /// Date in the format shown by the certificate website.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt = |date: &Date| date.format(DATE_FMT).unwrap();
+        match self {
+            Self::Day(date) => write!(f, "dia {}", fmt(date)),
+            Self::Period { start, end } => {
+                write!(f, "período de {} a {}", fmt(start), fmt(end))
+            }
+            Self::Days(days) => {
+                let days = days.iter().map(fmt).collect::<Vec<_>>();
+                write!(f, "dias {}", enumerate(&days))
+            }
         }
     }
 }
@@ -113,59 +442,262 @@ impl TryFrom<String> for EventDate {
     type Error = Box<dyn std::error::Error>;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        match value.split_once('-') {
-            Some((start, end)) => {
-                let start = Date::parse(start.trim(), DATE_FMT)?;
-                let end = Date::parse(end.trim(), DATE_FMT)?;
-                if start >= end {
-                    Err(ParseError::new("Start of the event happen before the end", value).into())
-                } else {
-                    Ok(Self::Period { start, end })
-                }
-            }
-            None => {
-                let day = Date::parse(value.trim(), DATE_FMT)?;
-                Ok(Self::Day(day))
-            }
-        }
+        Self::parse(&value, time::OffsetDateTime::now_utc().date())
     }
 }
 deserialize_fn!(parse_evt_date(): EventDate::try_from, String => EventDate);
 
 #[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "AttendeeRecord")]
 pub struct Attendee {
-    #[serde(rename = "NOME", deserialize_with = "parse_att_name")]
     pub name: String,
-    #[serde(rename = "CPF", deserialize_with = "parse_cpf")]
-    pub cpf: Cpf,
+    pub id: Identification,
+    pub workload: Workload,
+    pub role: Role,
+}
+
+/// A row of the attendees sheet.
+#[derive(Deserialize)]
+struct AttendeeRecord {
+    #[serde(rename = "NOME", deserialize_with = "parse_att_name")]
+    name: String,
+    #[serde(rename = "CPF", alias = "IDENTIFICACAO")]
+    id: String,
+    #[serde(rename = "TIPO_ID", default, deserialize_with = "parse_id_kind")]
+    kind: IdKind,
     #[serde(rename = "CH", deserialize_with = "parse_workload")]
-    pub workload: u32,
+    workload: Workload,
+    #[serde(rename = "FUNCAO", default, deserialize_with = "parse_role")]
+    role: Role,
+}
+
+impl TryFrom<AttendeeRecord> for Attendee {
+    type Error = ParseError<String>;
+
+    fn try_from(record: AttendeeRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: record.name,
+            id: Identification::new(record.kind, record.id)?,
+            workload: record.workload,
+            role: record.role,
+        })
+    }
 }
 
+/// What an attendee did in the event.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    #[default]
+    Participant,
+    Speaker,
+    Organizer,
+    Monitor,
+}
+
+impl Role {
+    /// Role name as written in the attendees sheet.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Participant => "participante",
+            Self::Speaker => "palestrante",
+            Self::Organizer => "organizador",
+            Self::Monitor => "monitor",
+        }
+    }
+
+    /// Which role prevails for someone listed with several roles, the
+    /// highest one.
+    pub fn priority(&self) -> u8 {
+        match self {
+            Self::Participant => 0,
+            Self::Monitor => 1,
+            Self::Organizer => 2,
+            Self::Speaker => 3,
+        }
+    }
+}
+
+impl TryFrom<String> for Role {
+    type Error = ParseError<String>;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let role = match value.trim().to_lowercase().as_str() {
+            "" | "participante" | "ouvinte" => Self::Participant,
+            "palestrante" | "ministrante" => Self::Speaker,
+            "organizador" | "organizadora" | "organização" | "organizacao" => Self::Organizer,
+            "monitor" | "monitora" => Self::Monitor,
+            _ => {
+                let err =
+                    ParseError::new("participante, palestrante, organizador or monitor", value);
+                return Err(err);
+            }
+        };
+        Ok(role)
+    }
+}
+deserialize_fn!(parse_role(): Role::try_from, String => Role);
+
 fn validate_att_name(name: String) -> Result<String, ParseError<String>> {
-    if name.is_empty() {
+    if name.trim().is_empty() {
         let err = ParseError::new("Non-empty attendee name", name);
         Err(err)
     } else {
-        // capitalize name
-        let name = name
-            .split_whitespace()
-            .map(|word| word[0..1].to_uppercase() + &word[1..].to_lowercase())
-            .collect::<Vec<_>>()
-            .join(" ");
-        Ok(name)
+        Ok(name::normalize(&name, NameCase::Normalize))
     }
 }
 deserialize_fn!(parse_att_name(): validate_att_name, String => String);
 
-fn validate_workload(workload: f64) -> Result<u32, ParseError<f64>> {
-    if workload > 0.0 {
-        Ok(workload.ceil() as u32)
-    } else {
-        Err(ParseError::new("Workload greater than 0.0", workload))
+/// Time an attendee spent in the event, with minute precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Workload {
+    minutes: u32,
+}
+
+impl Workload {
+    pub fn from_hours(hours: u32) -> Self {
+        Self {
+            minutes: hours * 60,
+        }
+    }
+
+    pub fn from_minutes(minutes: u32) -> Self {
+        Self { minutes }
+    }
+
+    pub fn minutes(&self) -> u32 {
+        self.minutes
+    }
+
+    /// Workload as hours and minutes, like "1h30".
+    pub fn hm(&self) -> String {
+        match self.minutes % 60 {
+            0 => format!("{}h", self.minutes / 60),
+            min => format!("{}h{min:02}", self.minutes / 60),
+        }
+    }
+}
+
+impl std::str::FromStr for Workload {
+    type Err = ParseError<String>;
+
+    /// Parse decimal hours ("1,5" or "1.5"), "1:30", "1h30" or "90min".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            ParseError::new(
+                "Workload greater than 0 like 1,5, 1:30, 1h30 or 90min",
+                s.to_owned(),
+            )
+        };
+        let value = s.trim().to_lowercase().replace(' ', "");
+        let hm = |hours: &str, minutes: &str| -> Option<u32> {
+            let minutes = minutes.trim_end_matches("min").trim_end_matches('m');
+            let minutes = match minutes {
+                "" => 0,
+                _ => minutes.parse::<u32>().ok().filter(|min| *min < 60)?,
+            };
+            hours
+                .parse::<u32>()
+                .ok()?
+                .checked_mul(60)?
+                .checked_add(minutes)
+        };
+        let minutes = if let Some(minutes) = value
+            .strip_suffix("min")
+            .or_else(|| value.strip_suffix('m'))
+            .filter(|rest| !rest.contains('h'))
+        {
+            minutes.parse::<u32>().ok()
+        } else if let Some((hours, minutes)) = value.split_once('h') {
+            hm(hours, minutes)
+        } else if let Some((hours, minutes)) = value.split_once(':') {
+            hm(hours, minutes).filter(|_| minutes.len() == 2)
+        } else {
+            value
+                .replace(',', ".")
+                .parse::<f64>()
+                .ok()
+                .filter(|hours| (0.0..=f64::from(u32::MAX) / 60.0).contains(hours))
+                .map(|hours| (hours * 60.0).round() as u32)
+        };
+        match minutes {
+            Some(minutes) if minutes > 0 => Ok(Self { minutes }),
+            _ => Err(err()),
+        }
+    }
+}
+
+impl std::fmt::Display for Workload {
+    /// Hours as accepted by the certificate website, like "2" or "1.5".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hours = format!("{:.2}", self.minutes as f64 / 60.0);
+        write!(f, "{}", hours.trim_end_matches('0').trim_end_matches('.'))
+    }
+}
+
+impl TryFrom<String> for Workload {
+    type Error = ParseError<String>;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Workload> for String {
+    fn from(workload: Workload) -> Self {
+        workload.to_string()
+    }
+}
+deserialize_fn!(parse_workload(): Workload::try_from, String => Workload);
+
+/// How workloads are rounded before being certified.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round up to whole hours.
+    #[default]
+    Ceil,
+    /// Round down to whole hours.
+    Floor,
+    /// Round to the nearest whole hour, half hours up.
+    Nearest,
+    /// Round to the nearest half hour, quarters up.
+    HalfHour,
+}
+
+impl Rounding {
+    /// The rounded workload, or `None` for workloads shorter than one step
+    /// that would round down to zero.
+    pub fn apply(&self, workload: Workload) -> Option<Workload> {
+        let (step, offset) = match self {
+            Self::Ceil => (60, 59),
+            Self::Floor => (60, 0),
+            Self::Nearest => (60, 30),
+            Self::HalfHour => (30, 15),
+        };
+        let minutes = workload.minutes.saturating_add(offset) / step * step;
+        (minutes > 0).then(|| Workload::from_minutes(minutes))
+    }
+}
+
+impl std::str::FromStr for Rounding {
+    type Err = ParseError<String>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rounding = match s.trim().to_lowercase().as_str() {
+            "ceil" => Self::Ceil,
+            "floor" => Self::Floor,
+            "nearest" => Self::Nearest,
+            "half-hour" => Self::HalfHour,
+            _ => {
+                return Err(ParseError::new(
+                    "ceil, floor, nearest or half-hour",
+                    s.to_owned(),
+                ))
+            }
+        };
+        Ok(rounding)
     }
 }
-deserialize_fn!(parse_workload(): validate_workload, f64 => u32);
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Cpf {
@@ -173,47 +705,94 @@ pub struct Cpf {
 }
 
 impl Cpf {
+    /// Parse a CPF written with or without the `000.000.000-00` mask.
+    ///
+    /// Up to two missing leading zeros, as left by spreadsheets that read the
+    /// CPF as a number, are restored. The CPF is always stored in its masked form.
     pub fn new(value: String) -> Result<Self, ParseError<String>> {
         let value = value.trim();
-        let err = Err(ParseError::new(
-            "Valid CPF",
-            value.to_owned(),
-        ));
-
-        let (nums, digit) = match value.split_once('-') {
-            Some(vals) => vals,
-            None => return err,
-        };
+        Self::digits(value)
+            .and_then(|digits| Self::from_digits(&digits))
+            .ok_or_else(|| ParseError::new("Valid CPF", value.to_owned()))
+    }
+
+    /// The 11 digits of a CPF written with or without the mask, or with only
+    /// 9 or 10 digits after losing its leading zeros.
+    fn digits(value: &str) -> Option<[u16; 11]> {
+        let mut digits = Vec::with_capacity(11);
+        for c in value.trim().chars() {
+            match c {
+                '0'..='9' => digits.push(c as u16 - '0' as u16),
+                '.' | '-' | ' ' => {}
+                _ => return None,
+            }
+        }
+        if !(9..=11).contains(&digits.len()) {
+            return None;
+        }
+        let mut padded = [0; 11];
+        padded[11 - digits.len()..].copy_from_slice(&digits);
+        Some(padded)
+    }
 
-        if digit.len() != 2 {
-            return err;
+    fn from_digits(digits: &[u16; 11]) -> Option<Self> {
+        // sequences like 111.111.111-11 pass the checksum but are invalid
+        if digits.iter().all(|&d| d == digits[0]) {
+            return None;
         }
 
         let mut nums_arr = [0; 9];
-        let mut i = 0;
-        for part in nums.split('.') {
-            if part.len() != 3 {
-                return err;
-            }
+        nums_arr.copy_from_slice(&digits[..9]);
+        let digit = (digits[9] * 10 + digits[10]) as u8;
+        if digit != Self::gen_digit(nums_arr) {
+            return None;
+        }
 
-            for c in part.chars() {
-                match c.to_digit(10) {
-                    Some(n) => {
-                        nums_arr[i] = n as u16
-                    },
-                    None => return err
+        let nums = digits
+            .iter()
+            .map(|d| char::from(b'0' + *d as u8))
+            .collect::<String>();
+        let id = format!(
+            "{}.{}.{}-{}",
+            &nums[0..3],
+            &nums[3..6],
+            &nums[6..9],
+            &nums[9..11]
+        );
+        Some(Self { id })
+    }
+
+    /// Valid CPFs a single typo away from `value`, either one wrong digit or
+    /// two swapped adjacent digits.
+    pub fn suggestions(value: &str) -> Vec<Self> {
+        let Some(digits) = Self::digits(value) else {
+            return Vec::new();
+        };
+        let mut candidates = Vec::new();
+        for i in 0..digits.len() {
+            for d in 0..10 {
+                if d != digits[i] {
+                    let mut typo = digits;
+                    typo[i] = d;
+                    candidates.push(typo);
                 }
-                i += 1;
+            }
+        }
+        for i in 1..digits.len() {
+            if digits[i - 1] != digits[i] {
+                let mut typo = digits;
+                typo.swap(i - 1, i);
+                candidates.push(typo);
             }
         }
 
-        let expected_digit = Self::gen_digit(nums_arr);
-        match digit.parse::<u8>() {
-            Ok(n) if n == expected_digit => Ok(Self {
-                id: value.to_owned(),
-            }),
-            _ => err,
+        let mut cpfs = Vec::<Self>::new();
+        for cpf in candidates.iter().filter_map(Self::from_digits) {
+            if !cpfs.contains(&cpf) {
+                cpfs.push(cpf);
+            }
         }
+        cpfs
     }
 
     fn gen_digit(nums: [u16; 9]) -> u8 {
@@ -233,11 +812,146 @@ impl Cpf {
         &self.id
     }
 }
-deserialize_fn!(parse_cpf(): Cpf::new, String => Cpf);
+
+/// Kind of document that identifies an attendee.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IdKind {
+    #[default]
+    Cpf,
+    Passport,
+    /// Brazilian foreigner registration, either RNE or its successor CRNM.
+    Rne,
+    /// Free-form id issued by an institution, like a student enrollment.
+    Institutional,
+}
+
+impl IdKind {
+    /// Kind name as written in the attendees sheet.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Cpf => "cpf",
+            Self::Passport => "passaporte",
+            Self::Rne => "rne",
+            Self::Institutional => "institucional",
+        }
+    }
+}
+
+impl TryFrom<String> for IdKind {
+    type Error = ParseError<String>;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let kind = match value.trim().to_lowercase().as_str() {
+            "" | "cpf" => Self::Cpf,
+            "passaporte" | "passport" => Self::Passport,
+            "rne" | "crnm" | "rne/crnm" => Self::Rne,
+            "institucional" | "matricula" | "matrícula" => Self::Institutional,
+            _ => {
+                let err = ParseError::new("cpf, passaporte, rne, crnm or institucional", value);
+                return Err(err);
+            }
+        };
+        Ok(kind)
+    }
+}
+deserialize_fn!(parse_id_kind(): IdKind::try_from, String => IdKind);
+
+/// Document that identifies an attendee in the `usuario.identificacao` column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Identification {
+    Cpf(Cpf),
+    Passport(String),
+    Rne(String),
+    Institutional(String),
+}
+
+impl Identification {
+    /// Parse `value` as an identification of the given kind.
+    pub fn new(kind: IdKind, value: String) -> Result<Self, ParseError<String>> {
+        match kind {
+            IdKind::Cpf => Cpf::new(value).map(Self::Cpf),
+            IdKind::Passport => Self::passport(value),
+            IdKind::Rne => Self::rne(value),
+            IdKind::Institutional => Self::institutional(value),
+        }
+    }
+
+    /// Passport numbers have from 5 to 12 letters and digits, stored uppercase.
+    fn passport(value: String) -> Result<Self, ParseError<String>> {
+        let number = value
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_uppercase();
+        if (5..=12).contains(&number.len()) && number.chars().all(|c| c.is_ascii_alphanumeric()) {
+            Ok(Self::Passport(number))
+        } else {
+            Err(ParseError::new("Valid passport number", value))
+        }
+    }
+
+    /// RNE and CRNM numbers are a letter, six digits and a check character,
+    /// stored in the `V000000-0` form.
+    fn rne(value: String) -> Result<Self, ParseError<String>> {
+        let number = value
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect::<String>()
+            .to_uppercase();
+        let chars = number.chars().collect::<Vec<_>>();
+        let valid = chars.len() == 8
+            && chars[0].is_ascii_alphabetic()
+            && chars[1..7].iter().all(char::is_ascii_digit)
+            && chars[7].is_ascii_alphanumeric();
+        if valid {
+            Ok(Self::Rne(format!("{}-{}", &number[..7], &number[7..])))
+        } else {
+            Err(ParseError::new("Valid RNE or CRNM", value))
+        }
+    }
+
+    /// Institutional ids accept letters, digits and the `.`, `-`, `/` and `_`
+    /// separators.
+    fn institutional(value: String) -> Result<Self, ParseError<String>> {
+        let id = value.trim();
+        let valid = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '.' | '-' | '/' | '_'));
+        if valid {
+            Ok(Self::Institutional(id.to_owned()))
+        } else {
+            Err(ParseError::new("Valid institutional id", value))
+        }
+    }
+
+    pub fn kind(&self) -> IdKind {
+        match self {
+            Self::Cpf(_) => IdKind::Cpf,
+            Self::Passport(_) => IdKind::Passport,
+            Self::Rne(_) => IdKind::Rne,
+            Self::Institutional(_) => IdKind::Institutional,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Cpf(cpf) => cpf.as_str(),
+            Self::Passport(id) | Self::Rne(id) | Self::Institutional(id) => id,
+        }
+    }
+}
+
+impl From<Cpf> for Identification {
+    fn from(cpf: Cpf) -> Self {
+        Self::Cpf(cpf)
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use time::macros::date;
 
     #[test]
     fn check_evt_name() {
@@ -255,11 +969,73 @@ mod tests {
         let desc = EventDesc::try_from("20".to_owned()).expect("Should be a valid description Id");
         assert_eq!(EventDesc::Id(20), desc);
 
-        let desc = EventDesc::try_from("Some text".to_owned())
+        let desc = EventDesc::try_from("Some text for #nome#".to_owned())
+            .expect("Should be a valid description Text");
+        assert_eq!(EventDesc::Text("Some text for #nome#".to_owned()), desc);
+
+        assert!(EventDesc::try_from("Some text".to_owned()).is_err());
+        assert!(EventDesc::try_from("Some text for #nome".to_owned()).is_err());
+        assert!(EventDesc::try_from("#nome# with #cargahoraria# hours".to_owned()).is_err());
+
+        let placeholders = Placeholders::new().required([]);
+        let desc = EventDesc::parse("Some text".to_owned(), &placeholders)
             .expect("Should be a valid description Text");
         assert_eq!(EventDesc::Text("Some text".to_owned()), desc);
     }
 
+    #[test]
+    fn check_evt_texts() {
+        let read = |csv: &str| {
+            csv::Reader::from_reader(csv.as_bytes())
+                .deserialize::<EventData>()
+                .next()
+                .expect("one event")
+        };
+        let csv = "NOME,DATA,TEXTO,TEXTO_PALESTRANTE\n\
+                   Event,04/05/2023,#nome# fez o #curso#,#nome# palestrou\n";
+        let data = read(csv).expect("placeholders checked later");
+        assert!(data.check_texts(&Placeholders::default()).is_err());
+        let placeholders = Placeholders::default().known(["nome".to_owned(), "curso".to_owned()]);
+        assert_eq!(Ok(()), data.check_texts(&placeholders));
+
+        let csv = "NOME,DATA,TEXTO,TEXTO_PALESTRANTE\n\
+                   Event,04/05/2023,#nome# participou,#nome palestrou\n";
+        assert!(read(csv).is_err());
+    }
+
+    #[test]
+    fn read_evt_data() {
+        let csv = "NOME,DATA,TEXTO,TEXTO_PALESTRANTE,TEXTO_MONITOR\n\
+                   Event,04/05/2023,#nome# participou,#nome# palestrou,\n";
+        let data: EventData = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .next()
+            .expect("one event")
+            .expect("valid event");
+
+        assert_eq!(1, data.roles.len());
+        assert_eq!(
+            &EventDesc::Text("#nome# participou".to_owned()),
+            data.desc(Role::Participant)
+        );
+        assert_eq!(
+            &EventDesc::Text("#nome# palestrou".to_owned()),
+            data.desc(Role::Speaker)
+        );
+        assert_eq!(
+            &EventDesc::Text("#nome# participou".to_owned()),
+            data.desc(Role::Monitor)
+        );
+
+        let csv = "NOME,DATA,TEXTO\nEvent,04/05/2023,10\n";
+        let data: EventData = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .next()
+            .expect("one event")
+            .expect("valid event");
+        assert!(data.roles.is_empty());
+    }
+
     #[test]
     fn check_evt_date() {
         assert!(EventDate::try_from("01/01/2023-42/54/3050".to_owned()).is_err());
@@ -278,27 +1054,202 @@ mod tests {
         );
     }
 
+    #[test]
+    fn evt_date_formats() {
+        let today = date!(2023 - 06 - 01);
+        let parse = |value: &str| EventDate::parse(value, today);
+
+        assert_eq!(
+            EventDate::Day(date!(2023 - 05 - 04)),
+            parse("2023-05-04").expect("Should be a valid event Day")
+        );
+        assert_eq!(
+            EventDate::Day(date!(2023 - 05 - 04)),
+            parse("04/05/23").expect("Should be a valid event Day")
+        );
+        let period = EventDate::Period {
+            start: date!(2023 - 05 - 04),
+            end: date!(2023 - 05 - 06),
+        };
+        for value in [
+            "2023-05-04 - 2023-05-06",
+            "2023-05-04 a 2023-05-06",
+            "04/05/23-06/05/2023",
+            "04/05/2023 até 06/05/2023",
+        ] {
+            assert_eq!(
+                period,
+                parse(value).expect("Should be a valid event Period")
+            );
+        }
+        assert!(parse("2023-05-06 - 2023-05-04").is_err());
+
+        assert_eq!(
+            EventDate::Day(date!(2023 - 05 - 04)),
+            parse("4/5/2023").expect("Should be a valid event Day")
+        );
+        assert_eq!(
+            period,
+            parse("4/5/2023 - 6/5/23").expect("Should be a valid event Period")
+        );
+        assert!(parse("4/5/202").is_err());
+
+        let days = EventDate::Days(vec![
+            date!(2023 - 05 - 04),
+            date!(2023 - 05 - 11),
+            date!(2023 - 05 - 18),
+        ]);
+        assert_eq!(
+            days,
+            parse("04, 11 e 18/05/2023").expect("Should be valid event Days")
+        );
+        assert_eq!(
+            days,
+            parse("18/05, 04 e 11/05/23").expect("Should be valid event Days")
+        );
+        assert_eq!("dias 04/05/2023, 11/05/2023 e 18/05/2023", days.to_string());
+        assert!(parse("04, 04/05/2023").is_err());
+        assert!(parse("04, 32/05/2023").is_err());
+
+        assert!(parse("01/06/2024").is_ok());
+        assert!(parse("01/06/2025").is_err());
+        assert!(parse("01/06/3050").is_err());
+        assert!(parse("04, 11/05/2099").is_err());
+    }
+
+    #[test]
+    fn long_evt_date() {
+        let today = date!(2024 - 06 - 01);
+        let long = |value: &str| {
+            EventDate::parse(value, today)
+                .expect("Should be a valid event date")
+                .long()
+        };
+
+        assert_eq!("4 de maio de 2023", long("04/05/2023"));
+        assert_eq!("4 a 6 de maio de 2023", long("04/05/2023 - 06/05/2023"));
+        assert_eq!(
+            "30 de abril a 2 de maio de 2023",
+            long("30/04/2023 - 02/05/2023")
+        );
+        assert_eq!(
+            "30 de dezembro de 2023 a 2 de janeiro de 2024",
+            long("30/12/2023 - 02/01/2024")
+        );
+        assert_eq!("4, 11 e 18 de maio de 2023", long("04, 11 e 18/05/2023"));
+        assert_eq!(
+            "27 de abril e 4 de maio de 2023",
+            long("27/04 e 04/05/2023")
+        );
+        assert_eq!(
+            "30 de dezembro de 2023 e 2 e 3 de janeiro de 2024",
+            long("30/12/2023, 02 e 03/01/2024")
+        );
+    }
+
     #[test]
     fn check_att_name() {
         assert!(validate_att_name("".to_owned()).is_err());
+        assert!(validate_att_name("  ".to_owned()).is_err());
         assert_eq!(
             Ok("John Will".to_owned()),
             validate_att_name(" john  will ".to_owned())
         );
+        assert_eq!(
+            Ok("Ângela da Silva".to_owned()),
+            validate_att_name("ÂNGELA DA SILVA".to_owned())
+        );
+    }
+
+    #[test]
+    fn check_att_role() {
+        assert_eq!(Ok(Role::Participant), Role::try_from("".to_owned()));
+        assert_eq!(
+            Ok(Role::Speaker),
+            Role::try_from(" Palestrante ".to_owned())
+        );
+        assert_eq!(
+            Ok(Role::Organizer),
+            Role::try_from("ORGANIZAÇÃO".to_owned())
+        );
+        assert_eq!(Ok(Role::Monitor), Role::try_from("monitor".to_owned()));
+        assert!(Role::try_from("chefe".to_owned()).is_err());
+
+        let csv = "NOME,CPF,CH\nA,762.050.858-95,1\n";
+        let att: Attendee = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .next()
+            .expect("one attendee")
+            .expect("valid attendee");
+        assert_eq!(Role::Participant, att.role);
+
+        let csv = "NOME,CPF,CH,FUNCAO\nA,762.050.858-95,1,monitor\n";
+        let att: Attendee = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .next()
+            .expect("one attendee")
+            .expect("valid attendee");
+        assert_eq!(Role::Monitor, att.role);
     }
 
     #[test]
     fn check_att_workload() {
-        assert!(validate_workload(-1.0).is_err());
-        assert!(validate_workload(0.0).is_err());
-        assert_eq!(Ok(2), validate_workload(1.3));
-        assert_eq!(Ok(5), validate_workload(5.0));
+        let parse = |value: &str| value.parse::<Workload>().map(|w| w.minutes());
+        assert!(parse("-1").is_err());
+        assert!(parse("0").is_err());
+        assert!(parse("0h").is_err());
+        assert!(parse("1:5").is_err());
+        assert!(parse("1h60").is_err());
+        assert!(parse("one hour").is_err());
+        assert!(parse("99999999h").is_err());
+        assert!(parse("71582788:30").is_err());
+        assert!(parse("99999999").is_err());
+        assert_eq!(Ok(78), parse("1.3"));
+        assert_eq!(Ok(300), parse("5"));
+        for value in [
+            "1,5", "1.5", "1:30", "1h30", "1h30min", "1 h 30", "90min", "90 m",
+        ] {
+            assert_eq!(Ok(90), parse(value));
+        }
+        assert_eq!(Ok(120), parse("2h"));
+
+        assert_eq!("2", Workload::from_hours(2).to_string());
+        assert_eq!("1.5", Workload::from_minutes(90).to_string());
+        assert_eq!("1.33", Workload::from_minutes(80).to_string());
+        assert_eq!("1h20", Workload::from_minutes(80).hm());
+    }
+
+    #[test]
+    fn round_att_workload() {
+        let round = |rounding: Rounding, minutes| {
+            rounding
+                .apply(Workload::from_minutes(minutes))
+                .map(|workload| workload.minutes())
+        };
+        assert_eq!(Some(120), round(Rounding::Ceil, 61));
+        assert_eq!(Some(60), round(Rounding::Ceil, 60));
+        assert_eq!(Some(60), round(Rounding::Ceil, 5));
+        assert_eq!(Some(60), round(Rounding::Floor, 119));
+        assert_eq!(None, round(Rounding::Floor, 20));
+        assert_eq!(Some(60), round(Rounding::Nearest, 89));
+        assert_eq!(Some(120), round(Rounding::Nearest, 90));
+        assert_eq!(None, round(Rounding::Nearest, 29));
+        assert_eq!(Some(90), round(Rounding::HalfHour, 80));
+        assert_eq!(Some(60), round(Rounding::HalfHour, 74));
+        assert_eq!(Some(30), round(Rounding::HalfHour, 15));
+        assert_eq!(None, round(Rounding::HalfHour, 5));
+        assert_eq!(Ok(Rounding::HalfHour), "half-hour".parse());
+        assert!("up".parse::<Rounding>().is_err());
     }
 
     #[test]
     fn check_att_cpf() {
-        assert!(Cpf::new("68116578553".to_owned()).is_err());
-        assert!(Cpf::new("633.834.740.89".to_owned()).is_err());
+        assert!(Cpf::new("".to_owned()).is_err());
+        assert!(Cpf::new("681.165.785-54".to_owned()).is_err());
+        assert!(Cpf::new("681.165.785-5a".to_owned()).is_err());
+        assert!(Cpf::new("681165785530".to_owned()).is_err());
+        assert!(Cpf::new("111.111.111-11".to_owned()).is_err());
+        assert!(Cpf::new("00000000000".to_owned()).is_err());
 
         let cpf1 = Cpf::new("762.050.858-95".to_owned()).expect("it should be a valid CPF");
         assert_eq!("762.050.858-95", cpf1.as_str());
@@ -306,4 +1257,94 @@ mod tests {
         let cpf2 = Cpf::new("   762.050.858-95 ".to_owned()).expect("it should be a valid CPF");
         assert_eq!(cpf1, cpf2);
     }
+
+    #[test]
+    fn check_att_id() {
+        let id = Identification::new(IdKind::Cpf, "68116578553".to_owned())
+            .expect("it should be a valid CPF");
+        assert_eq!("681.165.785-53", id.as_str());
+        assert!(Identification::new(IdKind::Cpf, "AB123456".to_owned()).is_err());
+
+        let id = Identification::new(IdKind::Passport, " ab 123456 ".to_owned())
+            .expect("it should be a valid passport");
+        assert_eq!(Identification::Passport("AB123456".to_owned()), id);
+        assert!(Identification::new(IdKind::Passport, "AB12".to_owned()).is_err());
+        assert!(Identification::new(IdKind::Passport, "AB-123456".to_owned()).is_err());
+
+        let id = Identification::new(IdKind::Rne, "v1234567".to_owned())
+            .expect("it should be a valid RNE");
+        assert_eq!("V123456-7", id.as_str());
+        assert!(Identification::new(IdKind::Rne, "1234567-8".to_owned()).is_err());
+
+        let id = Identification::new(IdKind::Institutional, " 2019.1/0042 ".to_owned())
+            .expect("it should be a valid institutional id");
+        assert_eq!("2019.1/0042", id.as_str());
+        assert!(Identification::new(IdKind::Institutional, " ".to_owned()).is_err());
+        assert!(Identification::new(IdKind::Institutional, "1'; --".to_owned()).is_err());
+
+        assert_eq!(Ok(IdKind::Rne), IdKind::try_from("CRNM".to_owned()));
+        assert!(IdKind::try_from("rg".to_owned()).is_err());
+
+        let csv = "NOME,CPF,TIPO_ID,CH\n\
+                   A,762.050.858-95,,1\n\
+                   B,AB123456,passaporte,1\n";
+        let atts = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .collect::<Result<Vec<Attendee>, _>>()
+            .expect("valid attendees");
+        assert_eq!(IdKind::Cpf, atts[0].id.kind());
+        assert_eq!(IdKind::Passport, atts[1].id.kind());
+
+        let csv = "NOME,IDENTIFICACAO,TIPO_ID,CH\nA,AB123456,cpf,1\n";
+        let att = csv::Reader::from_reader(csv.as_bytes())
+            .deserialize::<Attendee>()
+            .next()
+            .expect("one attendee");
+        assert!(att.is_err());
+    }
+
+    #[test]
+    fn cpf_suggestions() {
+        let suggestions = Cpf::suggestions("681.165.785-54");
+        assert!(suggestions.contains(&Cpf::new("681.165.785-53".to_owned()).unwrap()));
+        assert!(suggestions
+            .iter()
+            .all(|cpf| Cpf::new(cpf.as_str().to_owned()).is_ok()));
+
+        // swapped adjacent digits
+        let suggestions = Cpf::suggestions("681.165.758-53");
+        assert!(suggestions.contains(&Cpf::new("681.165.785-53".to_owned()).unwrap()));
+
+        assert!(Cpf::suggestions("681.165.785-5a").is_empty());
+        assert!(Cpf::suggestions("111.111.111-11")
+            .iter()
+            .all(|cpf| cpf.as_str() != "111.111.111-11"));
+    }
+
+    #[test]
+    fn canonical_att_cpf() {
+        let canonical = "681.165.785-53";
+        for cpf in [
+            "68116578553",
+            "681.165.785 53",
+            "681 165 785 53",
+            "681165785-53",
+        ] {
+            let cpf = Cpf::new(cpf.to_owned()).expect("it should be a valid CPF");
+            assert_eq!(canonical, cpf.as_str());
+        }
+
+        let cpf = Cpf::new("633.834.740.89".to_owned()).expect("it should be a valid CPF");
+        assert_eq!("633.834.740-89", cpf.as_str());
+
+        // leading zeros lost by spreadsheets
+        let cpf = Cpf::new("1234567890".to_owned()).expect("it should be a valid CPF");
+        assert_eq!("012.345.678-90", cpf.as_str());
+        let cpf = Cpf::new("123456797".to_owned()).expect("it should be a valid CPF");
+        assert_eq!("001.234.567-97", cpf.as_str());
+        // too short to be a CPF, even if 000.000.001-91 passes the checksum
+        for value in ["191", "1.91", "12345691"] {
+            assert!(Cpf::new(value.to_owned()).is_err(), "{value}");
+        }
+    }
 }
@@ -0,0 +1,305 @@
+use super::{
+    csv_data::{Attendee, Identification, ParseError, Role, Workload},
+    name,
+};
+
+/// How the workloads of a repeated attendee are merged.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WorkloadPolicy {
+    /// Add the workloads, for attendees listed once per activity.
+    Sum,
+    /// Keep the largest workload, for rows copied by mistake.
+    #[default]
+    Max,
+    /// Refuse repeated attendees.
+    Reject,
+}
+
+impl std::str::FromStr for WorkloadPolicy {
+    type Err = ParseError<String>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let policy = match s.trim().to_lowercase().as_str() {
+            "sum" => Self::Sum,
+            "max" => Self::Max,
+            "reject" => Self::Reject,
+            _ => return Err(ParseError::new("sum, max or reject", s.to_owned())),
+        };
+        Ok(policy)
+    }
+}
+
+/// Which name is kept when a repeated attendee has different names.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NamePolicy {
+    /// The name of the first row.
+    #[default]
+    First,
+    /// The longest name, usually the complete one.
+    Longest,
+    /// Ask which name to keep.
+    Ask,
+}
+
+impl std::str::FromStr for NamePolicy {
+    type Err = ParseError<String>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let policy = match s.trim().to_lowercase().as_str() {
+            "first" => Self::First,
+            "longest" => Self::Longest,
+            "ask" => Self::Ask,
+            _ => return Err(ParseError::new("first, longest or ask", s.to_owned())),
+        };
+        Ok(policy)
+    }
+}
+
+/// Rows of the same attendee merged into one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge {
+    pub id: Identification,
+    pub names: Vec<String>,
+    pub workloads: Vec<Workload>,
+    pub roles: Vec<Role>,
+    /// Merged attendee.
+    pub name: String,
+    pub workload: Workload,
+    pub role: Role,
+}
+
+impl std::fmt::Display for Merge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let workloads = self
+            .workloads
+            .iter()
+            .map(Workload::hm)
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "{}: {} ({workloads}) -> {} ({})",
+            self.id.as_str(),
+            self.names.join(" / "),
+            self.name,
+            self.workload.hm()
+        )?;
+        if self.roles.iter().any(|role| *role != self.role) {
+            let roles = self
+                .roles
+                .iter()
+                .map(Role::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, ", roles {roles} -> {}", self.role.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// Policies to merge attendees listed more than once.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Dedup {
+    pub workload: WorkloadPolicy,
+    pub name: NamePolicy,
+}
+
+impl Dedup {
+    /// Merge the attendees with the same identification, keeping the role of
+    /// highest [`Role::priority`]. With [`NamePolicy::Ask`], `ask` picks the
+    /// name to keep among the different names of an attendee.
+    pub fn merge<F>(
+        &self,
+        atts: Vec<Attendee>,
+        mut ask: F,
+    ) -> Result<(Vec<Attendee>, Vec<Merge>), ParseError<String>>
+    where
+        F: FnMut(&Identification, &[String]) -> String,
+    {
+        let mut groups: Vec<Vec<Attendee>> = Vec::new();
+        for att in atts {
+            match groups.iter_mut().find(|group| group[0].id == att.id) {
+                Some(group) => group.push(att),
+                None => groups.push(vec![att]),
+            }
+        }
+
+        let mut merged = Vec::with_capacity(groups.len());
+        let mut merges = Vec::new();
+        for group in groups {
+            if group.len() == 1 {
+                merged.extend(group);
+                continue;
+            }
+            let id = group[0].id.clone();
+            if self.workload == WorkloadPolicy::Reject {
+                let err = ParseError::new(
+                    "Attendees without repeated identification",
+                    id.as_str().to_owned(),
+                );
+                return Err(err);
+            }
+
+            let names = group.iter().map(|att| att.name.clone()).collect::<Vec<_>>();
+            let workloads = group.iter().map(|att| att.workload).collect::<Vec<_>>();
+            let roles = group.iter().map(|att| att.role).collect::<Vec<_>>();
+            let role = roles
+                .iter()
+                .copied()
+                .max_by_key(Role::priority)
+                .unwrap_or_default();
+            let minutes = workloads.iter().map(Workload::minutes);
+            let workload = Workload::from_minutes(match self.workload {
+                WorkloadPolicy::Sum => minutes.sum(),
+                _ => minutes.max().unwrap_or_default(),
+            });
+            let name = match self.name {
+                _ if names.iter().all(|name| *name == names[0]) => names[0].clone(),
+                NamePolicy::First => names[0].clone(),
+                NamePolicy::Longest => names
+                    .iter()
+                    .fold(&names[0], |longest, name| {
+                        if name.chars().count() > longest.chars().count() {
+                            name
+                        } else {
+                            longest
+                        }
+                    })
+                    .clone(),
+                NamePolicy::Ask => ask(&id, &names),
+            };
+
+            merged.push(Attendee {
+                name: name.clone(),
+                id: id.clone(),
+                workload,
+                role,
+            });
+            merges.push(Merge {
+                id,
+                names,
+                workloads,
+                roles,
+                name,
+                workload,
+                role,
+            });
+        }
+        Ok((merged, merges))
+    }
+}
+
+/// Pairs of attendees with different identifications but similar names,
+/// which may be the same person with a mistyped identification.
+pub fn similar_names(atts: &[Attendee]) -> Vec<(&Attendee, &Attendee)> {
+    let mut pairs = Vec::new();
+    for (i, a) in atts.iter().enumerate() {
+        for b in &atts[i + 1..] {
+            if a.id != b.id && name::similarity(&a.name, &b.name) >= name::MIN_SIMILARITY {
+                pairs.push((a, b));
+            }
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::csv_data::{Cpf, Role};
+
+    fn att(name: &str, cpf: &str, hours: u32) -> Attendee {
+        Attendee {
+            name: name.to_owned(),
+            id: Cpf::new(cpf.to_owned()).expect("valid cpf").into(),
+            workload: Workload::from_hours(hours),
+            role: Role::Participant,
+        }
+    }
+
+    fn atts() -> Vec<Attendee> {
+        vec![
+            att("Ana Silva", "762.050.858-95", 2),
+            att("Bia", "754.751.875-33", 1),
+            att("Ana Maria Silva", "76205085895", 3),
+        ]
+    }
+
+    #[test]
+    fn merge_policies() {
+        let never = |_: &Identification, _: &[String]| -> String { unreachable!() };
+        let dedup = Dedup::default();
+        let (merged, merges) = dedup.merge(atts(), never).expect("merged attendees");
+        assert_eq!(2, merged.len());
+        assert_eq!("Ana Silva", merged[0].name);
+        assert_eq!(Workload::from_hours(3), merged[0].workload);
+        assert_eq!(
+            vec!["Ana Silva".to_owned(), "Ana Maria Silva".to_owned()],
+            merges[0].names
+        );
+
+        let dedup = Dedup {
+            workload: WorkloadPolicy::Sum,
+            name: NamePolicy::Longest,
+        };
+        let (merged, _) = dedup.merge(atts(), never).expect("merged attendees");
+        assert_eq!("Ana Maria Silva", merged[0].name);
+        assert_eq!(Workload::from_hours(5), merged[0].workload);
+
+        let dedup = Dedup {
+            workload: WorkloadPolicy::Reject,
+            name: NamePolicy::First,
+        };
+        assert!(dedup.merge(atts(), never).is_err());
+        assert!(dedup.merge(atts()[..2].to_vec(), never).is_ok());
+    }
+
+    #[test]
+    fn ask_name() {
+        let dedup = Dedup {
+            workload: WorkloadPolicy::Max,
+            name: NamePolicy::Ask,
+        };
+        let (merged, merges) = dedup
+            .merge(atts(), |id, names| {
+                assert_eq!("762.050.858-95", id.as_str());
+                names[1].clone()
+            })
+            .expect("merged attendees");
+        assert_eq!("Ana Maria Silva", merged[0].name);
+        assert_eq!(
+            "762.050.858-95: Ana Silva / Ana Maria Silva (2h, 3h) -> Ana Maria Silva (3h)",
+            merges[0].to_string()
+        );
+    }
+
+    #[test]
+    fn merge_roles() {
+        let never = |_: &Identification, _: &[String]| -> String { unreachable!() };
+        let mut atts = atts();
+        atts[2].role = Role::Speaker;
+        atts.push(Attendee {
+            role: Role::Monitor,
+            ..atts[0].clone()
+        });
+        let (merged, merges) = Dedup::default()
+            .merge(atts, never)
+            .expect("merged attendees");
+        assert_eq!(Role::Speaker, merged[0].role);
+        assert!(merges[0]
+            .to_string()
+            .ends_with(", roles participante, palestrante, monitor -> palestrante"));
+    }
+
+    #[test]
+    fn similar_attendees() {
+        let atts = vec![
+            att("Ana Silva", "762.050.858-95", 2),
+            att("Bia", "754.751.875-33", 1),
+            att("ANA SILVA", "681.165.785-53", 3),
+        ];
+        let pairs = similar_names(&atts);
+        assert_eq!(1, pairs.len());
+        assert_eq!("681.165.785-53", pairs[0].1.id.as_str());
+    }
+}
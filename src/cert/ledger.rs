@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    code::Code,
+    csv_data::{Identification, Workload},
+    Event,
+};
+
+/// A certificate issued with a verification code.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Issued {
+    #[serde(rename = "CODIGO")]
+    pub code: Code,
+    #[serde(rename = "EVENTO")]
+    pub event: String,
+    #[serde(rename = "DATA")]
+    pub date: String,
+    #[serde(rename = "NOME")]
+    pub name: String,
+    /// CPF or other identification of the attendee.
+    #[serde(rename = "CPF")]
+    pub cpf: String,
+    #[serde(rename = "CH")]
+    pub workload: Workload,
+    /// Reason of the revocation of the certificate.
+    #[serde(rename = "REVOGADO", default)]
+    pub revoked: Option<String>,
+    /// Id of the event in the database, unknown for new events.
+    #[serde(rename = "EVENTO_ID", default)]
+    pub event_id: Option<u32>,
+}
+
+impl std::fmt::Display for Issued {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Code:     {}", self.code)?;
+        writeln!(f, "Event:    {}", self.event)?;
+        writeln!(f, "Date:     {}", self.date)?;
+        if !self.name.is_empty() {
+            writeln!(f, "Name:     {}", self.name)?;
+        }
+        writeln!(f, "CPF:      {}", self.cpf)?;
+        write!(f, "Workload: {}", self.workload.hm())?;
+        if let Some(reason) = &self.revoked {
+            write!(f, "\nRevoked:  {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Event {
+    /// Certificates issued for the event, empty if it has no verification codes.
+    pub fn issued(&self) -> Vec<Issued> {
+        self.atts
+            .iter()
+            .filter_map(|att| {
+                Some(Issued {
+                    code: self.code(att)?,
+                    event: self.data.name.clone(),
+                    date: self.data.date.to_string(),
+                    name: att.name.clone(),
+                    cpf: att.id.as_str().to_owned(),
+                    workload: att.workload,
+                    revoked: None,
+                    event_id: self.data.id,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A local export of the issued certificates.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Ledger {
+    entries: Vec<Issued>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a ledger from CSV.
+    pub fn read<R>(src: R) -> csv::Result<Self>
+    where
+        R: std::io::Read,
+    {
+        let entries = csv::Reader::from_reader(src)
+            .deserialize()
+            .collect::<csv::Result<Vec<_>>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Write the ledger as CSV.
+    pub fn write<W>(&self, dst: W) -> csv::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let mut wtr = csv::Writer::from_writer(dst);
+        for entry in &self.entries {
+            wtr.serialize(entry)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+
+    /// Add issued certificates replacing the ones with the same code.
+    pub fn add_many<I>(&mut self, issued: I)
+    where
+        I: IntoIterator<Item = Issued>,
+    {
+        for entry in issued {
+            self.entries.retain(|old| old.code != entry.code);
+            self.entries.push(entry);
+        }
+    }
+
+    pub fn entries(&self) -> &[Issued] {
+        &self.entries
+    }
+
+    /// Find the certificate with the given code.
+    pub fn find_code(&self, code: &Code) -> Option<&Issued> {
+        self.entries.iter().find(|entry| &entry.code == code)
+    }
+
+    /// Mark the certificate with the given code as revoked for `reason`,
+    /// returning it if found.
+    pub fn revoke(&mut self, code: &Code, reason: &str) -> Option<&Issued> {
+        let entry = self.entries.iter_mut().find(|entry| &entry.code == code)?;
+        entry.revoked = Some(reason.to_owned());
+        Some(entry)
+    }
+
+    /// Find the certificates of the identification `id`, optionally of a
+    /// single event.
+    pub fn find_id(&self, id: &Identification, event: Option<&str>) -> Vec<&Issued> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.cpf == id.as_str())
+            .filter(|entry| event.is_none_or(|name| entry.event == name))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{
+        code::Signer,
+        csv_data::{Attendee, Cpf, EventData, EventDate, EventDesc, IdKind, Role},
+    };
+    use std::collections::BTreeMap;
+    use time::macros::date;
+
+    fn signed_event() -> Event {
+        let data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Id(1),
+            date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::new(),
+            max_workload: None,
+        };
+        let att_a = Attendee {
+            name: "A".to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
+        };
+        let att_b = Attendee {
+            name: "B".to_owned(),
+            id: Identification::new(IdKind::Passport, "AB123456".to_owned())
+                .expect("valid passport"),
+            workload: Workload::from_hours(2),
+            role: Role::Participant,
+        };
+        data.into_event(vec![att_a, att_b])
+            .sign(Signer::new(b"secret"))
+    }
+
+    #[test]
+    fn event_issued() {
+        let event = signed_event();
+        let issued = event.issued();
+        assert_eq!(2, issued.len());
+        assert_eq!("dia 04/05/2023", issued[0].date);
+        assert_eq!("AB123456", issued[1].cpf);
+        assert_eq!(
+            Some(issued[1].code.clone()),
+            event.code(&event.attendees()[1])
+        );
+    }
+
+    #[test]
+    fn ledger_csv() {
+        let mut ledger = Ledger::new();
+        ledger.add_many(signed_event().issued());
+
+        let mut buffer = Vec::new();
+        ledger.write(&mut buffer).expect("valid CSV");
+        let csv = String::from_utf8(buffer.clone()).expect("valid UTF-8");
+        assert!(csv.starts_with("CODIGO,EVENTO,DATA,NOME,CPF,CH,REVOGADO,EVENTO_ID\n"));
+
+        assert_eq!(ledger, Ledger::read(buffer.as_slice()).expect("valid CSV"));
+
+        // ledgers written before revocations have no REVOGADO and EVENTO_ID
+        // columns
+        let old = csv
+            .replace(",REVOGADO,EVENTO_ID\n", "\n")
+            .replace(",,\n", "\n");
+        assert_eq!(ledger, Ledger::read(old.as_bytes()).expect("valid CSV"));
+    }
+
+    #[test]
+    fn ledger_revoke() {
+        let mut ledger = Ledger::new();
+        ledger.add_many(signed_event().issued());
+        let unknown = "WIXT-JH7G-FGZF-K4IY".parse().expect("valid code");
+        assert_eq!(None, ledger.revoke(&unknown, "Fraud"));
+        let code = ledger.entries()[0].code.clone();
+        let revoked = ledger.revoke(&code, "Fraud").expect("issued code");
+        assert_eq!("754.751.875-33", revoked.cpf);
+
+        let cpf = Cpf::new("754.751.875-33".to_owned()).expect("valid cpf");
+        let issued = ledger.find_id(&cpf.into(), None)[0];
+        assert_eq!(Some("Fraud".to_owned()), issued.revoked);
+        assert!(issued.to_string().ends_with("\nRevoked:  Fraud"));
+        assert_eq!(None, ledger.entries()[1].revoked);
+    }
+
+    #[test]
+    fn ledger_find() {
+        let mut ledger = Ledger::new();
+        ledger.add_many(signed_event().issued());
+        ledger.add_many(signed_event().issued());
+        assert_eq!(2, ledger.entries().len());
+
+        let issued = ledger.entries()[0].clone();
+        assert_eq!(Some(&issued), ledger.find_code(&issued.code));
+
+        let cpf = Cpf::new("754.751.875-33".to_owned())
+            .expect("valid cpf")
+            .into();
+        assert_eq!(vec![&issued], ledger.find_id(&cpf, None));
+        assert_eq!(vec![&issued], ledger.find_id(&cpf, Some("Event")));
+        assert!(ledger.find_id(&cpf, Some("Other")).is_empty());
+
+        let passport = Identification::new(IdKind::Passport, "ab 123456".to_owned()).unwrap();
+        assert_eq!(vec![&ledger.entries()[1]], ledger.find_id(&passport, None));
+
+        let mut event = signed_event();
+        event.data.id = Some(12);
+        assert!(event
+            .issued()
+            .iter()
+            .all(|issued| issued.event_id == Some(12)));
+    }
+}
@@ -0,0 +1,522 @@
+//! Attendance of online events from the participant reports of meeting
+//! platforms.
+
+use serde::Deserialize;
+use std::error::Error;
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+use super::{
+    csv_data::{Attendee, IdKind, Identification, ParseError, Role, Workload},
+    name::{self, NameCase},
+};
+
+/// Meeting platform that generated a participants report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Meet,
+    Zoom,
+    Jitsi,
+    Teams,
+}
+
+/// Report columns of a platform, each with its alternative names.
+struct Columns {
+    name: &'static [&'static str],
+    first_name: &'static [&'static str],
+    last_name: &'static [&'static str],
+    email: &'static [&'static str],
+    join: &'static [&'static str],
+    leave: &'static [&'static str],
+}
+
+impl Platform {
+    fn columns(&self) -> Columns {
+        match self {
+            Self::Meet => Columns {
+                name: &["Participant name", "Nome do participante"],
+                first_name: &["First name", "Nome"],
+                last_name: &["Last name", "Sobrenome"],
+                email: &["Email", "E-mail"],
+                join: &["Time joined", "Horário de entrada"],
+                leave: &["Time exited", "Horário de saída"],
+            },
+            Self::Zoom => Columns {
+                name: &[
+                    "Name (Original Name)",
+                    "Name (original name)",
+                    "Name",
+                    "Nome",
+                ],
+                first_name: &[],
+                last_name: &[],
+                email: &["User Email", "Email", "E-mail"],
+                join: &["Join Time", "Join time", "Horário de entrada"],
+                leave: &["Leave Time", "Leave time", "Horário de saída"],
+            },
+            Self::Jitsi => Columns {
+                name: &["Name", "Nome"],
+                first_name: &[],
+                last_name: &[],
+                email: &["Email", "E-mail"],
+                join: &["Join Time", "Joined", "Entrada"],
+                leave: &["Leave Time", "Left", "Saída"],
+            },
+            Self::Teams => Columns {
+                name: &["Full Name", "Name", "Nome"],
+                first_name: &[],
+                last_name: &[],
+                email: &["Email", "UPN", "E-mail"],
+                join: &["Join Time", "First Join", "Horário de entrada"],
+                leave: &["Leave Time", "Last Leave", "Horário de saída"],
+            },
+        }
+    }
+}
+
+impl std::str::FromStr for Platform {
+    type Err = ParseError<String>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let platform = match s.trim().to_lowercase().as_str() {
+            "meet" | "google-meet" => Self::Meet,
+            "zoom" => Self::Zoom,
+            "jitsi" => Self::Jitsi,
+            "teams" => Self::Teams,
+            _ => return Err(ParseError::new("meet, zoom, jitsi or teams", s.to_owned())),
+        };
+        Ok(platform)
+    }
+}
+
+/// Parse a report timestamp.
+///
+/// Dates may be ISO (`2023-05-04`) or use slashes, which are read as
+/// month first in 12-hour timestamps, as in the English reports, and as day
+/// first otherwise. The date is `None` in timestamps with only the time.
+fn parse_timestamp(value: &str) -> Option<(Option<Date>, Time)> {
+    let value = value.trim().to_uppercase();
+    let (value, pm) = match (value.strip_suffix("PM"), value.strip_suffix("AM")) {
+        (Some(value), _) => (value, Some(true)),
+        (_, Some(value)) => (value, Some(false)),
+        _ => (value.as_str(), None),
+    };
+    let mut date = None;
+    let mut time = None;
+    for part in value.split([' ', ',', 'T']).filter(|part| !part.is_empty()) {
+        if part.contains(':') {
+            let nums = part
+                .split(':')
+                .map(|num| num.parse::<u8>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            let (hour, min, sec) = match nums[..] {
+                [hour, min] => (hour, min, 0),
+                [hour, min, sec] => (hour, min, sec),
+                _ => return None,
+            };
+            let hour = match pm {
+                Some(true) if hour < 12 => hour + 12,
+                Some(false) if hour == 12 => 0,
+                _ => hour,
+            };
+            time = Some(Time::from_hms(hour, min, sec).ok()?);
+        } else {
+            let nums = part
+                .split(['/', '-'])
+                .map(|num| num.parse::<i32>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            let (year, month, day) = match nums[..] {
+                [year, month, day] if year > 31 => (year, month, day),
+                [month, day, year] if pm.is_some() => (year, month, day),
+                [day, month, year] => (year, month, day),
+                _ => return None,
+            };
+            let year = if year < 100 { year + 2000 } else { year };
+            let month = Month::try_from(u8::try_from(month).ok()?).ok()?;
+            date = Some(Date::from_calendar_date(year, month, u8::try_from(day).ok()?).ok()?);
+        }
+    }
+    Some((date, time?))
+}
+
+/// A connection to the meeting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Interval {
+    /// Report of the connection when its times have no date, since the same
+    /// clock times in different reports are different meetings.
+    report: Option<usize>,
+    join: PrimitiveDateTime,
+    leave: PrimitiveDateTime,
+}
+
+/// Someone who joined the meeting, identified by email or name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub name: String,
+    pub email: String,
+    intervals: Vec<Interval>,
+}
+
+/// Total time covered by `intervals`, counting overlapping ones of the same
+/// report or date once.
+fn merged_duration(mut intervals: Vec<Interval>) -> Workload {
+    intervals.sort();
+    let mut merged: Vec<Interval> = Vec::new();
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if last.report == interval.report && interval.join <= last.leave => {
+                last.leave = interval.leave.max(last.leave)
+            }
+            _ => merged.push(interval),
+        }
+    }
+    let seconds = merged
+        .iter()
+        .map(|interval| (interval.leave - interval.join).whole_seconds())
+        .sum::<i64>();
+    Workload::from_minutes((seconds / 60) as u32)
+}
+
+impl Participant {
+    /// Time in the meeting, counting overlapping connections once.
+    pub fn duration(&self) -> Workload {
+        merged_duration(self.intervals.clone())
+    }
+
+    fn key(&self) -> String {
+        if self.email.is_empty() {
+            name::fold(&self.name)
+        } else {
+            self.email.to_lowercase()
+        }
+    }
+}
+
+/// Participants of one or more meeting reports.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Report {
+    participants: Vec<Participant>,
+    /// Number of reports read.
+    reports: usize,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a participants report of the given platform.
+    pub fn read<R>(&mut self, platform: Platform, src: R) -> Result<(), Box<dyn Error>>
+    where
+        R: std::io::Read,
+    {
+        let columns = platform.columns();
+        let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(src);
+        let headers = rdr.headers()?.clone();
+        let col = |names: &[&str]| {
+            headers.iter().position(|col| {
+                names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(col.trim()))
+            })
+        };
+        let name_col = col(columns.name);
+        let first_col = col(columns.first_name);
+        let last_col = col(columns.last_name);
+        let email_col = col(columns.email);
+        let (join_col, leave_col) = match (col(columns.join), col(columns.leave)) {
+            (Some(join), Some(leave)) => (join, leave),
+            _ => {
+                let err =
+                    ParseError::new("Join and leave time columns", headers.as_slice().to_owned());
+                return Err(err.into());
+            }
+        };
+
+        for record in rdr.records() {
+            let record = record?;
+            let field =
+                |col: Option<usize>| col.and_then(|i| record.get(i)).unwrap_or_default().trim();
+            let name = match name_col {
+                Some(_) => field(name_col).to_owned(),
+                None => format!("{} {}", field(first_col), field(last_col)),
+            };
+            let name = name::normalize(&name, NameCase::Preserve);
+            if name.is_empty() {
+                continue;
+            }
+            let time = |col| {
+                let value = field(Some(col));
+                parse_timestamp(value)
+                    .ok_or_else(|| ParseError::new("Join or leave time", value.to_owned()))
+            };
+            let ((join_date, join), (leave_date, leave)) = (time(join_col)?, time(leave_col)?);
+            let report = match (join_date, leave_date) {
+                (Some(_), Some(_)) => None,
+                _ => Some(self.reports),
+            };
+            let undated = time::macros::date!(2000 - 01 - 01);
+            let join = PrimitiveDateTime::new(join_date.unwrap_or(undated), join);
+            let mut leave = PrimitiveDateTime::new(leave_date.unwrap_or(undated), leave);
+            if leave < join && report.is_some() {
+                // times without date that cross midnight
+                leave += time::Duration::days(1);
+            }
+
+            let participant = Participant {
+                name,
+                email: field(email_col).to_owned(),
+                intervals: vec![Interval {
+                    report,
+                    join,
+                    leave,
+                }],
+            };
+            let key = participant.key();
+            match self.participants.iter_mut().find(|p| p.key() == key) {
+                Some(found) => found.intervals.extend(participant.intervals),
+                None => self.participants.push(participant),
+            }
+        }
+        self.reports += 1;
+        Ok(())
+    }
+
+    pub fn participants(&self) -> &[Participant] {
+        &self.participants
+    }
+}
+
+/// An event registration linking an attendee email to their identification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registration {
+    pub name: String,
+    pub email: String,
+    pub id: Identification,
+    pub role: Role,
+}
+
+#[derive(Deserialize)]
+struct RegistrationRecord {
+    #[serde(rename = "NOME")]
+    name: String,
+    #[serde(rename = "EMAIL", default)]
+    email: String,
+    #[serde(rename = "CPF", alias = "IDENTIFICACAO")]
+    id: String,
+    #[serde(rename = "TIPO_ID", default)]
+    kind: String,
+    #[serde(rename = "FUNCAO", default)]
+    role: String,
+}
+
+/// Registrations of an event.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Registrations {
+    entries: Vec<Registration>,
+}
+
+impl Registrations {
+    /// Read registrations from a CSV with the attendees sheet columns, except
+    /// `CH`, plus an `EMAIL` column.
+    pub fn read<R>(src: R) -> Result<Self, Box<dyn Error>>
+    where
+        R: std::io::Read,
+    {
+        let mut entries = Vec::new();
+        for record in csv::Reader::from_reader(src).deserialize() {
+            let record: RegistrationRecord = record?;
+            let kind = IdKind::try_from(record.kind)?;
+            entries.push(Registration {
+                name: name::normalize(&record.name, NameCase::Normalize),
+                email: record.email.trim().to_lowercase(),
+                id: Identification::new(kind, record.id)?,
+                role: Role::try_from(record.role)?,
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[Registration] {
+        &self.entries
+    }
+
+    /// Registration of a participant matched by email or, failing that, by a
+    /// single similar enough name.
+    pub fn find(&self, participant: &Participant) -> Option<&Registration> {
+        let email = participant.email.trim().to_lowercase();
+        if let Some(found) = self
+            .entries
+            .iter()
+            .find(|entry| !email.is_empty() && entry.email == email)
+        {
+            return Some(found);
+        }
+
+        let mut scores = self
+            .entries
+            .iter()
+            .map(|entry| (name::similarity(&participant.name, &entry.name), entry))
+            .filter(|(score, _)| *score >= name::MIN_SIMILARITY)
+            .collect::<Vec<_>>();
+        scores.sort_by(|a, b| b.0.total_cmp(&a.0));
+        match scores[..] {
+            [(_, entry)] => Some(entry),
+            [(best, entry), (second, _), ..] if best > second => Some(entry),
+            _ => None,
+        }
+    }
+}
+
+/// Attendees of the report matched to their registrations, with the time
+/// spent in the meeting as workload, and the unmatched participants.
+pub fn import(report: &Report, registrations: &Registrations) -> (Vec<Attendee>, Vec<Participant>) {
+    let mut matched: Vec<(&Registration, Vec<_>)> = Vec::new();
+    let mut unmatched = Vec::new();
+    for participant in report.participants() {
+        match registrations.find(participant) {
+            Some(reg) => match matched.iter_mut().find(|(found, _)| found.id == reg.id) {
+                // the same person connected with different names or emails
+                Some((_, intervals)) => intervals.extend(participant.intervals.clone()),
+                None => matched.push((reg, participant.intervals.clone())),
+            },
+            None => unmatched.push(participant.clone()),
+        }
+    }
+
+    let attendees = matched
+        .into_iter()
+        .map(|(reg, intervals)| Attendee {
+            name: reg.name.clone(),
+            id: reg.id.clone(),
+            workload: merged_duration(intervals),
+            role: reg.role,
+        })
+        .filter(|att| att.workload.minutes() > 0)
+        .collect();
+    (attendees, unmatched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::{date, time};
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(
+            Some((Some(date!(2023 - 05 - 04)), time!(19:01:02))),
+            parse_timestamp("05/04/2023 07:01:02 PM")
+        );
+        assert_eq!(
+            Some((Some(date!(2023 - 05 - 04)), time!(00:05:00))),
+            parse_timestamp("5/4/23, 12:05:00 AM")
+        );
+        assert_eq!(
+            Some((Some(date!(2023 - 05 - 04)), time!(19:01:00))),
+            parse_timestamp("04/05/2023 19:01")
+        );
+        assert_eq!(
+            Some((Some(date!(2023 - 05 - 04)), time!(19:01:02))),
+            parse_timestamp("2023-05-04T19:01:02")
+        );
+        assert_eq!(Some((None, time!(10:02))), parse_timestamp("10:02 AM"));
+        assert_eq!(None, parse_timestamp("yesterday"));
+        assert_eq!(None, parse_timestamp("04/05/2023"));
+    }
+
+    #[test]
+    fn merge_intervals() {
+        let csv = "Name (Original Name),User Email,Join Time,Leave Time\n\
+                   Ana,ana@mail.com,05/04/2023 07:00:00 PM,05/04/2023 08:00:00 PM\n\
+                   Ana (phone),ANA@mail.com,05/04/2023 07:30:00 PM,05/04/2023 08:30:00 PM\n\
+                   Ana,ana@mail.com,05/04/2023 09:00:00 PM,05/04/2023 09:15:00 PM\n\
+                   Bia,,05/04/2023 07:00:00 PM,05/04/2023 07:10:00 PM\n";
+        let mut report = Report::new();
+        report
+            .read(Platform::Zoom, csv.as_bytes())
+            .expect("valid report");
+
+        let participants = report.participants();
+        assert_eq!(2, participants.len());
+        assert_eq!(Workload::from_minutes(105), participants[0].duration());
+        assert_eq!(Workload::from_minutes(10), participants[1].duration());
+    }
+
+    #[test]
+    fn merge_reports() {
+        let day_one = "Name,Email,Join Time,Leave Time\n\
+                       Ana,ana@mail.com,7:00 PM,8:00 PM\n\
+                       Ana,ana@mail.com,7:30 PM,8:30 PM\n";
+        let day_two = "Name,Email,Join Time,Leave Time\n\
+                       Ana,ana@mail.com,7:00 PM,8:00 PM\n";
+        let mut report = Report::new();
+        for csv in [day_one, day_two] {
+            report
+                .read(Platform::Jitsi, csv.as_bytes())
+                .expect("valid report");
+        }
+        assert_eq!(
+            Workload::from_minutes(150),
+            report.participants()[0].duration()
+        );
+
+        let dated = "Name,Email,Join Time,Leave Time\n\
+                     Ana,ana@mail.com,2023-05-04 19:00,2023-05-04 20:00\n";
+        let mut report = Report::new();
+        for _ in 0..2 {
+            report
+                .read(Platform::Jitsi, dated.as_bytes())
+                .expect("valid report");
+        }
+        assert_eq!(Workload::from_hours(1), report.participants()[0].duration());
+    }
+
+    #[test]
+    fn meet_report() {
+        let csv = "First name,Last name,Email,Duration,Time joined,Time exited\n\
+                   Ana,Silva,,1 hr,7:00 PM,8:00 PM\n\
+                   Bia,Souza,,1 hr,11:30 PM,12:30 AM\n";
+        let mut report = Report::new();
+        report
+            .read(Platform::Meet, csv.as_bytes())
+            .expect("valid report");
+
+        assert_eq!("Ana Silva", report.participants()[0].name);
+        assert_eq!(Workload::from_hours(1), report.participants()[1].duration());
+        assert!(report
+            .read(Platform::Meet, "Name,Email\nAna,\n".as_bytes())
+            .is_err());
+    }
+
+    #[test]
+    fn match_registrations() {
+        let regs = "NOME,EMAIL,CPF,FUNCAO\n\
+                    Ana Conceição da Silva,ana@mail.com,762.050.858-95,\n\
+                    Bia Souza,,754.751.875-33,monitor\n";
+        let regs = Registrations::read(regs.as_bytes()).expect("valid registrations");
+        let csv = "Name,Email,Join Time,Leave Time\n\
+                   Ana,Ana@Mail.com,2023-05-04 19:00,2023-05-04 20:00\n\
+                   Ana C Silva,,2023-05-04 19:30,2023-05-04 20:30\n\
+                   BIA SOUZA,,2023-05-04 19:00,2023-05-04 19:40\n\
+                   Bea Sousa,,2023-05-04 19:00,2023-05-04 19:40\n\
+                   Caio,caio@mail.com,2023-05-04 19:00,2023-05-04 19:40\n";
+        let mut report = Report::new();
+        report
+            .read(Platform::Jitsi, csv.as_bytes())
+            .expect("valid report");
+
+        let (attendees, unmatched) = import(&report, &regs);
+        assert_eq!(2, attendees.len());
+        assert_eq!("Ana Conceição da Silva", attendees[0].name);
+        assert_eq!(Workload::from_minutes(60), attendees[0].workload);
+        assert_eq!("Bia Souza", attendees[1].name);
+        assert_eq!(Role::Monitor, attendees[1].role);
+        assert_eq!(Workload::from_minutes(40), attendees[1].workload);
+
+        let unmatched = unmatched
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Ana C Silva", "Bea Sousa", "Caio"], unmatched);
+    }
+}
@@ -1,7 +1,24 @@
-use crate::sql::{QueryPool, ToSQL};
+use crate::{
+    sql::{quote, QueryPool, Schema, ToSQL},
+    template::{self, Template, TemplateError},
+};
+use minijinja::{context, Value as TemplateValue};
 
+pub mod attendance;
+pub mod code;
+pub mod correction;
 pub mod csv_data;
-use csv_data::{Attendee, EventData, EventDesc};
+pub mod duplicate;
+pub mod ledger;
+pub mod meeting;
+pub mod name;
+pub mod reconcile;
+pub mod revocation;
+pub mod text;
+pub mod typo;
+use code::{Code, Signer};
+use csv_data::{Attendee, EventData, EventDesc, Role, Rounding, Workload};
+use std::collections::BTreeSet;
 
 #[derive(Debug)]
 pub struct Certificate {
@@ -10,35 +27,119 @@ pub struct Certificate {
 }
 
 impl ToSQL for Certificate {
-    fn to_sql(&self) -> QueryPool {
+    fn to_sql_with(&self, schema: &Schema) -> QueryPool {
         let mut pool = QueryPool::new();
+        let evento = schema.table("evento");
+        let [id_col, nome, data, img] = schema.columns("evento", ["id", "nome", "data", "img"]);
 
-        // insert event
-        pool.add(format!(
-            "INSERT IGNORE INTO evento (nome, data, img) VALUES ('{}', '{}', '{}')",
-            self.event.data.name,
-            self.event.data.date.to_string(),
-            self.img
-        ));
+        match self.event.data.id {
+            // get the existing event id, where a missing event makes the
+            // subquery return two rows and abort the import
+            Some(id) => pool.add(format!(
+                "SET @evid := (SELECT {id_col} FROM {evento} WHERE {id_col}={id} \
+                UNION ALL SELECT NULL FROM (SELECT 1 UNION ALL SELECT 2) AS missing \
+                WHERE NOT EXISTS (SELECT {id_col} FROM {evento} WHERE {id_col}={id}))"
+            )),
+            None => {
+                // insert event
+                let (name, date, img_path) = (
+                    quote(&self.event.data.name),
+                    quote(&self.event.data.date.to_string()),
+                    quote(&self.img),
+                );
+                pool.add(format!(
+                    "INSERT IGNORE INTO {evento} ({nome}, {data}, {img}) \
+                    VALUES ({name}, {date}, {img_path})"
+                ));
 
-        // get event id
-        pool.add(format!(
-            "SET @evid := (SELECT id FROM evento WHERE nome='{}' AND data='{}' AND img='{}')",
-            self.event.data.name,
-            self.event.data.date.to_string(),
-            self.img
-        ));
+                // get event id
+                pool.add(format!(
+                    "SET @evid := (SELECT {id_col} FROM {evento} \
+                    WHERE {nome}={name} AND {data}={date} AND {img}={img_path})"
+                ));
+            }
+        }
 
         // add event queries
-        pool.add_many(self.event.to_sql());
+        pool.add_many(self.event.to_sql_with(schema));
         pool
     }
 }
 
+/// Template of the certificate SQL generated by [`ToSQL`].
+pub const SQL_TEMPLATE: &str = include_str!("certificate.sql");
+
+impl Certificate {
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+
+    /// Path of the certificate image in the site.
+    pub fn img(&self) -> &str {
+        &self.img
+    }
+
+    /// Variables of the SQL template: `event.id` (none for new events),
+    /// `event.name`, `event.date`, `img`, `codes`, the `texts` list with
+    /// `var`, `id` and `text`, the `attendees` list with `name`, `id`,
+    /// `workload`, `text` and `code`, and `schema.database` and the table
+    /// and column names, like `schema.evento` and `schema.evento.nome`.
+    pub fn context(&self, schema: &Schema) -> TemplateValue {
+        let event = &self.event;
+        let texts = event
+            .texts()
+            .into_iter()
+            .map(|(var, desc)| {
+                let (id, text) = match desc {
+                    EventDesc::Id(id) => (Some(*id), None),
+                    EventDesc::Text(text) => (None, Some(text.clone())),
+                };
+                context! { var, id, text }
+            })
+            .collect::<Vec<_>>();
+        let attendees = event
+            .atts
+            .iter()
+            .map(|att| {
+                context! {
+                    name => att.name,
+                    id => att.id.as_str(),
+                    workload => att.workload.to_string(),
+                    text => event.txt_var(att.role),
+                    code => event.code(att).map(|code| code.to_string()),
+                }
+            })
+            .collect::<Vec<_>>();
+        context! {
+            event => context! {
+                id => event.data.id,
+                name => event.data.name,
+                date => event.data.date.to_string(),
+            },
+            img => self.img,
+            codes => event.signer.is_some(),
+            texts,
+            attendees,
+            schema => template::schema_value(schema),
+        }
+    }
+
+    /// Generate the SQL through a template instead of the built-in queries.
+    pub fn render_sql(
+        &self,
+        template: &Template,
+        schema: &Schema,
+    ) -> Result<QueryPool, TemplateError> {
+        let sql = template.render(self.context(schema))?;
+        Ok(QueryPool::from_script(&sql))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Event {
     data: EventData,
     atts: Vec<Attendee>,
+    signer: Option<Signer>,
 }
 
 impl Event {
@@ -53,26 +154,236 @@ impl Event {
     pub fn into_cert(self, img: String) -> Certificate {
         Certificate { event: self, img }
     }
-}
 
-impl ToSQL for Event {
-    fn to_sql(&self) -> QueryPool {
-        let mut pool = QueryPool::new();
-        pool.add_many(self.atts.to_sql());
+    /// Issue a verification code for each participation.
+    pub fn sign(mut self, signer: Signer) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Round the attendee workloads returning the ones that changed or that
+    /// are too short to be rounded.
+    pub fn round(&mut self, rounding: Rounding) -> Vec<Rounded> {
+        let mut rounded = Vec::new();
+        for att in &mut self.atts {
+            let workload = rounding.apply(att.workload);
+            if workload != Some(att.workload) {
+                rounded.push(Rounded {
+                    name: att.name.clone(),
+                    from: att.workload,
+                    to: workload,
+                });
+                att.workload = workload.unwrap_or(att.workload);
+            }
+        }
+        rounded
+    }
+
+    /// Verification code of the given attendee, if codes are issued.
+    pub fn code(&self, att: &Attendee) -> Option<Code> {
+        self.signer
+            .as_ref()
+            .map(|signer| signer.code(&self.data, att))
+    }
+
+    /// Values of the certificate text placeholders for the given attendee.
+    pub fn placeholders(&self, att: &Attendee) -> Vec<(&'static str, String)> {
+        let mut values = vec![
+            ("nome", att.name.clone()),
+            ("identificacao", att.id.as_str().to_owned()),
+            ("evento", self.data.name.clone()),
+            ("cargaHoraria", att.workload.to_string()),
+            ("data", self.data.date.to_string()),
+        ];
+        if let Some(code) = self.code(att) {
+            values.push(("codigo", code.to_string()));
+        }
+        values
+    }
+
+    /// SQL variable with the text id of the given role.
+    fn txt_var(&self, role: Role) -> String {
+        if self.data.roles.contains_key(&role) {
+            format!("@txtid_{}", role.as_str())
+        } else {
+            "@txtid".to_owned()
+        }
+    }
 
-        // insert event text
-        if let EventDesc::Text(txt) = &self.data.desc {
-            pool.add(format!("INSERT IGNORE INTO texto (texto) VALUES ('{txt}')"));
+    /// SQL variables of the event texts with their descriptions, starting
+    /// with the default text and followed by the texts of the roles present.
+    fn texts(&self) -> Vec<(String, &EventDesc)> {
+        let roles = self
+            .atts
+            .iter()
+            .map(|att| att.role)
+            .filter(|role| self.data.roles.contains_key(role))
+            .collect::<BTreeSet<_>>();
+        std::iter::once(("@txtid".to_owned(), &self.data.desc))
+            .chain(
+                roles
+                    .into_iter()
+                    .map(|role| (self.txt_var(role), self.data.desc(role))),
+            )
+            .collect()
+    }
+
+    /// Fill `text` with the data of the given attendee.
+    pub fn att_text(&self, att: &Attendee, text: &str) -> String {
+        text::fill(text, self.placeholders(att))
+    }
+
+    /// Check the event before generating its SQL, where `img` is the name of
+    /// the certificate image, only optional for existing events.
+    pub fn validate(&self, img: Option<&str>) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        if self.atts.is_empty() {
+            issues.push(Issue::error("The event has no attendees".to_owned()));
+        }
+        let descs = std::iter::once(&self.data.desc).chain(self.data.roles.values());
+        if descs
+            .into_iter()
+            .any(|desc| matches!(desc, EventDesc::Text(txt) if txt.trim().is_empty()))
+        {
+            issues.push(Issue::error("The event text is missing".to_owned()));
+        }
+        let descs = std::iter::once(&self.data.desc).chain(self.data.roles.values());
+        for name in descs.filter_map(EventDesc::catalog_name) {
+            issues.push(Issue::error(format!(
+                "The event text @{name} needs an organization catalog"
+            )));
         }
 
-        // get description id
-        let part = match &self.data.desc {
-            EventDesc::Id(id) => format!("SET @txtid = {id}"),
-            EventDesc::Text(txt) => {
-                format!("SET @txtid := (SELECT id FROM texto WHERE texto='{txt}')")
+        let days = self.data.date.days();
+        let possible = Workload::from_hours(days * 24);
+        for att in &self.atts {
+            if att.workload > possible {
+                issues.push(Issue::error(format!(
+                    "{} has {} in an event of {days} day(s)",
+                    att.name,
+                    att.workload.hm()
+                )));
+            } else if let Some(max) = self.data.max_workload.filter(|max| att.workload > *max) {
+                issues.push(Issue::warning(format!(
+                    "{} has {}, more than the event maximum of {}",
+                    att.name,
+                    att.workload.hm(),
+                    max.hm()
+                )));
             }
+        }
+
+        let Some(img) = img else {
+            if self.data.id.is_none() {
+                issues.push(Issue::error(
+                    "A new event needs a certificate image".to_owned(),
+                ));
+            }
+            return issues;
         };
-        pool.add(part);
+        if img.trim().is_empty() {
+            issues.push(Issue::error(
+                "The certificate image name is empty".to_owned(),
+            ));
+        } else if img.contains(['\'', '"', '\\', '/']) {
+            issues.push(Issue::error(format!(
+                "The certificate image name {img} has quotes or slashes"
+            )));
+        } else {
+            let ext = img.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+            if !matches!(ext.as_deref(), Some("png" | "jpg" | "jpeg")) {
+                issues.push(Issue::warning(format!(
+                    "The certificate image {img} is not a PNG or JPEG file"
+                )));
+            }
+            if !img.is_ascii() {
+                issues.push(Issue::warning(format!(
+                    "The certificate image name {img} has non-ASCII characters"
+                )));
+            }
+        }
+        issues
+    }
+}
+
+/// How serious an event [`Issue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Makes the generated SQL invalid or wrong.
+    Error,
+    /// Probably a mistake, but the SQL is still valid.
+    Warning,
+}
+
+/// A problem found by [`Event::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "error: {}", self.message),
+            Severity::Warning => write!(f, "warning: {}", self.message),
+        }
+    }
+}
+
+/// An attendee workload changed by a rounding policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rounded {
+    pub name: String,
+    pub from: Workload,
+    /// `None` for workloads shorter than one rounding step, which are kept.
+    pub to: Option<Workload>,
+}
+
+impl ToSQL for Event {
+    fn to_sql_with(&self, schema: &Schema) -> QueryPool {
+        let mut pool = QueryPool::new();
+        pool.add_many(self.atts.to_sql_with(schema));
+        let texto = schema.table("texto");
+        let [txt_id, txt_col] = schema.columns("texto", ["id", "texto"]);
+        let usuario = schema.table("usuario");
+        let [user_id, identificacao] = schema.columns("usuario", ["id", "identificacao"]);
+        let participacao = schema.table("participacao");
+        let [p_user, p_event, p_text, ch, codigo] = schema.columns(
+            "participacao",
+            ["usuario", "evento", "texto", "ch", "codigo"],
+        );
+
+        // insert event texts and get their ids
+        for (var, desc) in self.texts() {
+            let part = match desc {
+                EventDesc::Id(id) => format!("SET {var} = {id}"),
+                EventDesc::Text(txt) => {
+                    let txt = quote(txt);
+                    pool.add(format!(
+                        "INSERT IGNORE INTO {texto} ({txt_col}) VALUES ({txt})"
+                    ));
+                    format!("SET {var} := (SELECT {txt_id} FROM {texto} WHERE {txt_col}={txt})")
+                }
+            };
+            pool.add(part);
+        }
 
         let values = self
             .atts
@@ -81,30 +392,59 @@ impl ToSQL for Event {
             .map(|(i, att)| {
                 // get user id
                 pool.add(format!(
-                    "SET @uid{i} := (SELECT id FROM usuario WHERE identificacao='{}')",
-                    att.cpf.as_str()
+                    "SET @uid{i} := (SELECT {user_id} FROM {usuario} WHERE {identificacao}={})",
+                    quote(att.id.as_str())
                 ));
-                format!("(@uid{i}, @evid, @txtid, {})", att.workload)
+                let txt = self.txt_var(att.role);
+                match self.code(att) {
+                    Some(code) => format!(
+                        "@uid{i}, @evid, {txt}, {}, {}",
+                        att.workload,
+                        quote(&code.to_string())
+                    ),
+                    None => format!("@uid{i}, @evid, {txt}, {}", att.workload),
+                }
             })
-            .collect::<Vec<_>>()
-            .join(",");
-        pool.add(format!(
-            "INSERT INTO participacao (usuario, evento, texto, ch) VALUES {values}"
-        ));
+            .collect::<Vec<_>>();
+        let columns = match self.signer {
+            Some(_) => format!("{p_user}, {p_event}, {p_text}, {ch}, {codigo}"),
+            None => format!("{p_user}, {p_event}, {p_text}, {ch}"),
+        };
+        if self.data.id.is_some() {
+            // only add the participations missing from the existing event
+            for (i, row) in values.iter().enumerate() {
+                pool.add(format!(
+                    "INSERT INTO {participacao} ({columns}) SELECT {row} FROM DUAL \
+                    WHERE NOT EXISTS (SELECT {p_user} FROM {participacao} \
+                    WHERE {p_user}=@uid{i} AND {p_event}=@evid)"
+                ));
+            }
+        } else {
+            let values = values
+                .iter()
+                .map(|row| format!("({row})"))
+                .collect::<Vec<_>>()
+                .join(",");
+            pool.add(format!(
+                "INSERT INTO {participacao} ({columns}) VALUES {values}"
+            ));
+        }
         pool
     }
 }
 
 impl ToSQL for Vec<Attendee> {
-    fn to_sql(&self) -> QueryPool {
+    fn to_sql_with(&self, schema: &Schema) -> QueryPool {
         let mut pool = QueryPool::new();
+        let usuario = schema.table("usuario");
+        let [nome, identificacao] = schema.columns("usuario", ["nome", "identificacao"]);
         let vals = self
             .iter()
-            .map(|att| format!("('{}', '{}')", att.name, att.cpf.as_str()))
+            .map(|att| format!("({}, {})", quote(&att.name), quote(att.id.as_str())))
             .collect::<Vec<_>>()
             .join(",");
         pool.add(format!(
-            "INSERT IGNORE INTO usuario (nome, identificacao) VALUES {vals}"
+            "INSERT IGNORE INTO {usuario} ({nome}, {identificacao}) VALUES {vals}"
         ));
         pool
     }
@@ -113,20 +453,27 @@ impl ToSQL for Vec<Attendee> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cert::csv_data::{Cpf, EventData, EventDate, EventDesc};
+    use crate::cert::csv_data::{Cpf, EventData, EventDate, EventDesc, Role};
+    use std::collections::BTreeMap;
     use time::macros::date;
 
     #[test]
     fn atts_to_sql() {
         let att_a = Attendee {
             name: "A".to_owned(),
-            cpf: Cpf::new("207.062.844-29".to_owned()).expect("valid cpf"),
-            workload: 1,
+            id: Cpf::new("207.062.844-29".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
         };
         let att_b = Attendee {
             name: "B".to_owned(),
-            cpf: Cpf::new("647.748.630-09".to_owned()).expect("valid cpf"),
-            workload: 1,
+            id: Cpf::new("647.748.630-09".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
         };
         let pool = vec![att_a, att_b].to_sql();
 
@@ -136,22 +483,149 @@ mod tests {
         assert_eq!(result, pool.to_string());
     }
 
+    #[test]
+    fn atts_to_sql_with_schema() {
+        let config = crate::config::Config::parse(
+            "[schema]\n\
+             usuario = \"users\"\n\
+             \"usuario.nome\" = \"name\"\n\
+             \"usuario.identificacao\" = \"document\"\n",
+        )
+        .expect("valid config");
+        let schema = Schema::from_config(&config).expect("valid schema");
+        let att = Attendee {
+            name: "A".to_owned(),
+            id: Cpf::new("207.062.844-29".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
+        };
+        assert_eq!(
+            "INSERT IGNORE INTO users (name, document) VALUES ('A', '207.062.844-29');\n",
+            vec![att].to_sql_with(&schema).to_string()
+        );
+    }
+
+    #[test]
+    fn round_workloads() {
+        let data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Id(1),
+            date: EventDate::Day(date!(2023 - 05 - 04)),
+            roles: BTreeMap::new(),
+            max_workload: None,
+        };
+        let att = |name: &str, minutes| Attendee {
+            name: name.to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_minutes(minutes),
+            role: Role::Participant,
+        };
+        let mut short = data.clone().into_event(vec![att("A", 20), att("B", 120)]);
+        assert_eq!(
+            vec![Rounded {
+                name: "A".to_owned(),
+                from: Workload::from_minutes(20),
+                to: None,
+            }],
+            short.round(Rounding::Floor)
+        );
+        assert_eq!(Workload::from_minutes(20), short.attendees()[0].workload);
+
+        let mut event = data.into_event(vec![att("A", 80), att("B", 90)]);
+        let rounded = event.round(Rounding::HalfHour);
+        assert_eq!(
+            vec![Rounded {
+                name: "A".to_owned(),
+                from: Workload::from_minutes(80),
+                to: Some(Workload::from_minutes(90)),
+            }],
+            rounded
+        );
+        assert!(event.to_sql().to_string().contains(
+            "INSERT INTO participacao (usuario, evento, texto, ch) \
+            VALUES (@uid0, @evid, @txtid, 1.5),(@uid1, @evid, @txtid, 1.5)"
+        ));
+    }
+
+    #[test]
+    fn validate_event() {
+        let data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Id(1),
+            date: EventDate::Period {
+                start: date!(2023 - 05 - 04),
+                end: date!(2023 - 05 - 05),
+            },
+            roles: BTreeMap::new(),
+            max_workload: Some(Workload::from_hours(8)),
+        };
+        let att = |name: &str, hours| Attendee {
+            name: name.to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(hours),
+            role: Role::Participant,
+        };
+
+        let event = data.clone().into_event(vec![att("A", 4)]);
+        assert!(event.validate(Some("cert.png")).is_empty());
+
+        let event = data.clone().into_event(vec![att("A", 50), att("B", 10)]);
+        let issues = event
+            .validate(Some("cert.svg"))
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "error: A has 50h in an event of 2 day(s)",
+                "warning: B has 10h, more than the event maximum of 8h",
+                "warning: The certificate image cert.svg is not a PNG or JPEG file",
+            ],
+            issues
+        );
+
+        let event = data.into_event(Vec::new());
+        let severities = event
+            .validate(Some("o'cert.png"))
+            .iter()
+            .map(|issue| issue.severity)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Severity::Error, Severity::Error], severities);
+    }
+
     #[test]
     fn event_to_sql() {
         let data = EventData {
+            id: None,
             name: "Event".to_owned(),
             desc: EventDesc::Text("Some description".to_owned()),
             date: EventDate::Day(date!(2023 - 05 - 04)),
+            roles: BTreeMap::new(),
+            max_workload: None,
         };
         let att_a = Attendee {
             name: "A".to_owned(),
-            cpf: Cpf::new("754.751.875-33".to_owned()).expect("valid cpf"),
-            workload: 1,
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
         };
         let att_b = Attendee {
             name: "B".to_owned(),
-            cpf: Cpf::new("647.748.630-09".to_owned()).expect("valid cpf"),
-            workload: 2,
+            id: Cpf::new("647.748.630-09".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(2),
+            role: Role::Participant,
         };
         let atts = vec![att_a, att_b];
         let atts_sql = atts.to_sql().to_string();
@@ -174,22 +648,117 @@ mod tests {
         assert_eq!(result, event.to_sql().to_string());
     }
 
+    #[test]
+    fn roles_to_sql() {
+        let data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Text("#nome# participou".to_owned()),
+            date: EventDate::Day(date!(2023 - 05 - 04)),
+            roles: BTreeMap::from([
+                (Role::Speaker, EventDesc::Id(7)),
+                (
+                    Role::Monitor,
+                    EventDesc::Text("#nome# monitorou".to_owned()),
+                ),
+            ]),
+            max_workload: None,
+        };
+        let att_a = Attendee {
+            name: "A".to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Speaker,
+        };
+        let att_b = Attendee {
+            name: "B".to_owned(),
+            id: Cpf::new("647.748.630-09".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(2),
+            role: Role::Organizer,
+        };
+        let atts = vec![att_a, att_b];
+        let atts_sql = atts.to_sql().to_string();
+        let event = data.into_event(atts);
+
+        let mut result = atts_sql;
+        result.push_str(
+            &[
+                "INSERT IGNORE INTO texto (texto) VALUES ('#nome# participou')",
+                "SET @txtid := (SELECT id FROM texto WHERE texto='#nome# participou')",
+                "SET @txtid_palestrante = 7",
+                "SET @uid0 := (SELECT id FROM usuario WHERE identificacao='754.751.875-33')",
+                "SET @uid1 := (SELECT id FROM usuario WHERE identificacao='647.748.630-09')",
+                "INSERT INTO participacao (usuario, evento, texto, ch) \
+                VALUES (@uid0, @evid, @txtid_palestrante, 1),(@uid1, @evid, @txtid, 2);\n",
+            ]
+            .join(";\n"),
+        );
+
+        assert_eq!(result, event.to_sql().to_string());
+    }
+
+    #[test]
+    fn signed_event_to_sql() {
+        let data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Id(1),
+            date: EventDate::Day(date!(2023 - 05 - 04)),
+            roles: BTreeMap::new(),
+            max_workload: None,
+        };
+        let att = Attendee {
+            name: "A".to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
+        };
+        let signer = Signer::new(b"secret");
+        let code = signer.code(&data, &att);
+        let event = data.into_event(vec![att.clone()]).sign(signer);
+
+        assert_eq!(Some(code.clone()), event.code(&att));
+        assert!(event.to_sql().to_string().contains(&format!(
+            "INSERT INTO participacao (usuario, evento, texto, ch, codigo) \
+            VALUES (@uid0, @evid, @txtid, 1, '{code}');\n"
+        )));
+        assert_eq!(
+            format!("A: {code}"),
+            event.att_text(&att, "#nome#: #codigo#")
+        );
+    }
+
     #[test]
     fn create_cert() {
         let data = EventData {
+            id: None,
             name: "Event".to_owned(),
             desc: EventDesc::Text("Some description".to_owned()),
             date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::new(),
+            max_workload: None,
         };
         let att_a = Attendee {
             name: "A".to_owned(),
-            cpf: Cpf::new("754.751.875-33".to_owned()).expect("valid cpf"),
-            workload: 1,
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
         };
         let att_b = Attendee {
             name: "B".to_owned(),
-            cpf: Cpf::new("647.748.630-09".to_owned()).expect("valid cpf"),
-            workload: 2,
+            id: Cpf::new("647.748.630-09".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(2),
+            role: Role::Participant,
         };
         let event = data.into_event(vec![att_a, att_b]);
         let cert = event.clone().into_cert("cert.png".to_owned());
@@ -198,22 +767,60 @@ mod tests {
         assert_eq!(cert.event, event);
     }
 
+    #[test]
+    fn att_text() {
+        let data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Id(1),
+            date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::new(),
+            max_workload: None,
+        };
+        let att = Attendee {
+            name: "A".to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(2),
+            role: Role::Participant,
+        };
+        let event = data.into_event(vec![att.clone()]);
+
+        assert_eq!(
+            "A (754.751.875-33) participou do Event com 2 hora(s) no dia 04/05/2023.",
+            event.att_text(
+                &att,
+                "#nome# (#identificacao#) participou do #evento# com #cargaHoraria# hora(s) no #data#."
+            )
+        );
+    }
+
     #[test]
     fn cert_to_sql() {
         let data = EventData {
+            id: None,
             name: "Event".to_owned(),
             desc: EventDesc::Text("Some description".to_owned()),
             date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::new(),
+            max_workload: None,
         };
         let att_a = Attendee {
             name: "A".to_owned(),
-            cpf: Cpf::new("754.751.875-33".to_owned()).expect("valid cpf"),
-            workload: 1,
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
         };
         let att_b = Attendee {
             name: "B".to_owned(),
-            cpf: Cpf::new("647.748.630-09".to_owned()).expect("valid cpf"),
-            workload: 2,
+            id: Cpf::new("647.748.630-09".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(2),
+            role: Role::Participant,
         };
         let event = data.into_event(vec![att_a, att_b]);
         let event_sql = event.to_sql().to_string();
@@ -229,4 +836,162 @@ mod tests {
 
         assert_eq!(result, cert.to_sql().to_string());
     }
+
+    #[test]
+    fn quote_cert_sql() {
+        let data = EventData {
+            id: None,
+            name: "Semana d'Ávila".to_owned(),
+            desc: EventDesc::Text("#nome# participou do #evento# \\o/".to_owned()),
+            date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::new(),
+            max_workload: None,
+        };
+        let att = Attendee {
+            name: "Conan O'Brien".to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
+        };
+        let sql = data
+            .into_event(vec![att])
+            .into_cert("cert.png".to_owned())
+            .to_sql()
+            .to_string();
+        assert!(sql.contains("VALUES ('Semana d''Ávila', 'dia 04/05/2023', 'cert.png')"));
+        assert!(sql.contains("WHERE nome='Semana d''Ávila' AND"));
+        assert!(sql.contains("VALUES ('#nome# participou do #evento# \\\\o/')"));
+        assert!(sql.contains("VALUES ('Conan O''Brien', '754.751.875-33')"));
+    }
+
+    #[test]
+    fn existing_event_to_sql() {
+        let data = EventData {
+            id: Some(12),
+            name: "Event".to_owned(),
+            desc: EventDesc::Id(3),
+            date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::new(),
+            max_workload: None,
+        };
+        let att = Attendee {
+            name: "A".to_owned(),
+            id: Cpf::new("754.751.875-33".to_owned())
+                .expect("valid cpf")
+                .into(),
+            workload: Workload::from_hours(1),
+            role: Role::Participant,
+        };
+        let event = data.into_event(vec![att]);
+        assert!(event.validate(None).is_empty());
+
+        let result = [
+            "USE petcomp",
+            "SET @evid := (SELECT id FROM evento WHERE id=12 \
+            UNION ALL SELECT NULL FROM (SELECT 1 UNION ALL SELECT 2) AS missing \
+            WHERE NOT EXISTS (SELECT id FROM evento WHERE id=12))",
+            "INSERT IGNORE INTO usuario (nome, identificacao) VALUES ('A', '754.751.875-33')",
+            "SET @txtid = 3",
+            "SET @uid0 := (SELECT id FROM usuario WHERE identificacao='754.751.875-33')",
+            "INSERT INTO participacao (usuario, evento, texto, ch) \
+            SELECT @uid0, @evid, @txtid, 1 FROM DUAL WHERE NOT EXISTS \
+            (SELECT usuario FROM participacao WHERE usuario=@uid0 AND evento=@evid)",
+            "",
+        ]
+        .join(";\n");
+        let cert = event.into_cert(String::new());
+        assert_eq!(result, cert.to_sql().into_req("petcomp").to_string());
+    }
+
+    #[test]
+    fn template_to_sql() {
+        let mut data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Text("#nome# participou".to_owned()),
+            date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::from([
+                (Role::Speaker, EventDesc::Id(7)),
+                (
+                    Role::Monitor,
+                    EventDesc::Text("#nome# monitorou".to_owned()),
+                ),
+            ]),
+            max_workload: None,
+        };
+        let atts = vec![
+            Attendee {
+                name: "A".to_owned(),
+                id: Cpf::new("754.751.875-33".to_owned())
+                    .expect("valid cpf")
+                    .into(),
+                workload: Workload::from_hours(1),
+                role: Role::Speaker,
+            },
+            Attendee {
+                name: "B".to_owned(),
+                id: Cpf::new("647.748.630-09".to_owned())
+                    .expect("valid cpf")
+                    .into(),
+                workload: Workload::from_hours(2),
+                role: Role::Monitor,
+            },
+        ];
+        let template = SQL_TEMPLATE.parse::<Template>().expect("valid template");
+        let schema = Schema::from_config(
+            &crate::config::Config::parse(
+                "[schema]\nevento = \"atividade\"\n\"participacao.ch\" = \"horas\"",
+            )
+            .expect("valid config"),
+        )
+        .expect("valid schema");
+
+        let mut certs = vec![data
+            .clone()
+            .into_event(atts.clone())
+            .into_cert("cert.png".to_owned())];
+        certs.push(
+            data.clone()
+                .into_event(atts.clone())
+                .sign(Signer::new(b"secret"))
+                .into_cert("cert.png".to_owned()),
+        );
+        data.id = Some(12);
+        certs.push(
+            data.clone()
+                .into_event(atts.clone())
+                .into_cert(String::new()),
+        );
+        certs.push(
+            data.into_event(atts)
+                .sign(Signer::new(b"secret"))
+                .into_cert(String::new()),
+        );
+        for cert in &certs {
+            for schema in [&Schema::default(), &schema] {
+                assert_eq!(
+                    cert.to_sql_with(schema).into_req("petcomp").to_string(),
+                    cert.render_sql(&template, schema)
+                        .expect("rendered template")
+                        .into_req("petcomp")
+                        .to_string()
+                );
+            }
+        }
+
+        let template = "{% for att in attendees %}\n\
+                        CALL emitir({{att.id|quote}}, {{event.id}}, {{att.workload}});\n\
+                        {% endfor %}"
+            .parse::<Template>()
+            .expect("valid template");
+        assert_eq!(
+            "CALL emitir('754.751.875-33', 12, 1);\nCALL emitir('647.748.630-09', 12, 2);\n",
+            certs[2]
+                .render_sql(&template, &Schema::default())
+                .expect("rendered template")
+                .to_string()
+        );
+    }
 }
@@ -0,0 +1,168 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Portuguese particles kept lowercase inside a name.
+pub const PARTICLES: &[&str] = &["da", "das", "de", "do", "dos", "e"];
+
+/// Least similarity between two names for them to be taken as the same.
+pub const MIN_SIMILARITY: f64 = 0.92;
+
+/// How the casing of a name is handled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NameCase {
+    /// Capitalize each word keeping the particles lowercase.
+    #[default]
+    Normalize,
+    /// Keep the casing exactly as given.
+    Preserve,
+}
+
+/// Uppercase the first grapheme of `word` and lowercase the rest, unless the
+/// rest is already in mixed case like in "McDonald".
+fn capitalize(word: &str) -> String {
+    let mut graphemes = word.graphemes(true);
+    let first = match graphemes.next() {
+        Some(first) => first.to_uppercase(),
+        None => return String::new(),
+    };
+    let rest = graphemes.as_str();
+    let mixed = rest.chars().any(char::is_uppercase) && rest.chars().any(char::is_lowercase);
+    if mixed {
+        first + rest
+    } else {
+        first + &rest.to_lowercase()
+    }
+}
+
+/// Capitalize each part of a word split by hyphens and apostrophes.
+fn capitalize_parts(word: &str, first_word: bool) -> String {
+    let mut result = String::with_capacity(word.len());
+    let mut part = String::new();
+    let mut first_part = true;
+    for c in word.chars() {
+        if matches!(c, '-' | '\'' | '’') {
+            // elisions like "d'Ávila" keep the particle lowercase
+            let elision = matches!(c, '\'' | '’') && part.to_lowercase() == "d";
+            if elision && !(first_word && first_part) {
+                result.push_str(&part.to_lowercase());
+            } else {
+                result.push_str(&capitalize(&part));
+            }
+            result.push(c);
+            part.clear();
+            first_part = false;
+        } else {
+            part.push(c);
+        }
+    }
+    result.push_str(&capitalize(&part));
+    result
+}
+
+/// Normalize an attendee name collapsing any whitespace between its words.
+pub fn normalize(name: &str, case: NameCase) -> String {
+    // zero width characters usually come from copying names from web pages
+    let name = name.replace(['\u{200b}', '\u{feff}'], "");
+    let words = name.split_whitespace();
+    match case {
+        NameCase::Preserve => words.collect::<Vec<_>>().join(" "),
+        NameCase::Normalize => words
+            .enumerate()
+            .map(|(i, word)| {
+                let lower = word.to_lowercase();
+                if i > 0 && PARTICLES.contains(&lower.as_str()) {
+                    lower
+                } else {
+                    capitalize_parts(word, i == 0)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Lowercase `name` without accents nor extra whitespace, to compare names
+/// written in different ways.
+pub fn fold(name: &str) -> String {
+    normalize(name, NameCase::Preserve)
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à'..='å' => 'a',
+            'ç' => 'c',
+            'è'..='ë' => 'e',
+            'ì'..='ï' => 'i',
+            'ñ' => 'n',
+            'ò'..='ö' | 'ø' => 'o',
+            'ù'..='ü' => 'u',
+            'ý' | 'ÿ' => 'y',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Similarity between two names from 0 to 1, ignoring case and accents.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    strsim::jaro_winkler(&fold(a), &fold(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multibyte_names() {
+        let case = NameCase::Normalize;
+        assert_eq!("Ângela", normalize("ângela", case));
+        assert_eq!("Érica Araújo", normalize("ÉRICA ARAÚJO", case));
+        // a decomposed "ñ" is a single grapheme
+        assert_eq!("N\u{303}andu", normalize("n\u{303}ANDU", case));
+    }
+
+    #[test]
+    fn particles() {
+        let case = NameCase::Normalize;
+        assert_eq!("Maria da Silva", normalize("MARIA DA SILVA", case));
+        assert_eq!(
+            "João dos Santos e Souza",
+            normalize("joão Dos santos E souza", case)
+        );
+        assert_eq!("Da Silva", normalize("da silva", case));
+    }
+
+    #[test]
+    fn hyphens_and_apostrophes() {
+        let case = NameCase::Normalize;
+        assert_eq!("Ana-Luísa Pereira", normalize("ana-luísa pereira", case));
+        assert_eq!("Joana d'Ávila", normalize("JOANA D'ÁVILA", case));
+        assert_eq!("Sean O'Brien", normalize("sean o'brien", case));
+        assert_eq!("D'Ávila", normalize("d'ávila", case));
+    }
+
+    #[test]
+    fn mixed_case() {
+        let case = NameCase::Normalize;
+        assert_eq!("Ronald McDonald", normalize("ronald McDonald", case));
+        assert_eq!("Mcdonald", normalize("MCDONALD", case));
+    }
+
+    #[test]
+    fn folding() {
+        assert_eq!("joao da conceicao", fold(" JOÃO  da Conceição"));
+        assert_eq!(fold("Ângela Araújo"), fold("angela araujo"));
+        assert_eq!(1.0, similarity("Ângela Araújo", "ANGELA ARAUJO"));
+        assert!(similarity("Ângela Araújo", "Angela Araujo Silva") >= MIN_SIMILARITY);
+        assert!(similarity("Ângela Araújo", "Pedro Araújo") < MIN_SIMILARITY);
+    }
+
+    #[test]
+    fn whitespace() {
+        assert_eq!(
+            "John Will",
+            normalize(" john \t will\u{a0} ", NameCase::Normalize)
+        );
+        assert_eq!(
+            "joão DA silva",
+            normalize("  joão\u{2003}DA \u{200b}  silva ", NameCase::Preserve)
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use serde::Deserialize;
+use std::error::Error;
+
+use super::{
+    csv_data::{Attendee, IdKind, Identification, Role, Workload},
+    meeting::{Registration, Registrations},
+    name::{self, NameCase},
+};
+
+/// A row of the attendance sheet of a reconciliation, where the
+/// identification may be missing when the attendee left only an email.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Presence {
+    pub name: String,
+    pub id: Option<Identification>,
+    pub email: String,
+    pub workload: Workload,
+    pub role: Role,
+}
+
+#[derive(Deserialize)]
+struct PresenceRecord {
+    #[serde(rename = "NOME")]
+    name: String,
+    #[serde(rename = "CPF", alias = "IDENTIFICACAO", default)]
+    id: String,
+    #[serde(rename = "TIPO_ID", default)]
+    kind: String,
+    #[serde(rename = "EMAIL", default)]
+    email: String,
+    #[serde(rename = "CH")]
+    workload: Workload,
+    #[serde(rename = "FUNCAO", default)]
+    role: String,
+}
+
+/// Read an attendance sheet with the attendees sheet columns plus an
+/// optional `EMAIL` column.
+pub fn read_presences<R>(src: R) -> Result<Vec<Presence>, Box<dyn Error>>
+where
+    R: std::io::Read,
+{
+    let mut presences = Vec::new();
+    for record in csv::Reader::from_reader(src).deserialize() {
+        let record: PresenceRecord = record?;
+        let id = match record.id.trim() {
+            "" => None,
+            _ => Some(Identification::new(
+                IdKind::try_from(record.kind)?,
+                record.id,
+            )?),
+        };
+        presences.push(Presence {
+            name: name::normalize(&record.name, NameCase::Normalize),
+            id,
+            email: record.email.trim().to_lowercase(),
+            workload: record.workload,
+            role: Role::try_from(record.role)?,
+        });
+    }
+    Ok(presences)
+}
+
+/// Outcome of reconciling an attendee.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    /// Registered and present.
+    Ok,
+    /// Registered but absent.
+    NoShow,
+    /// Present without registration.
+    WalkIn,
+    /// Present under a name different from the registered one.
+    NameMismatch { registered: String },
+    /// Matched by email but with another identification registered.
+    IdMismatch { registered: Identification },
+}
+
+impl Status {
+    /// Short label of the status.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::NoShow => "no-show",
+            Self::WalkIn => "walk-in",
+            Self::NameMismatch { .. } => "name mismatch",
+            Self::IdMismatch { .. } => "cpf mismatch",
+        }
+    }
+
+    /// Details of a mismatch.
+    pub fn detail(&self) -> String {
+        match self {
+            Self::NameMismatch { registered } => format!("registered as {registered}"),
+            Self::IdMismatch { registered } => {
+                format!("registered with {}", registered.as_str())
+            }
+            _ => String::new(),
+        }
+    }
+}
+
+/// An attendee in the reconciliation report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub status: Status,
+    pub name: String,
+    pub id: Option<Identification>,
+    pub email: String,
+    /// Attended workload, missing for no-shows.
+    pub workload: Option<Workload>,
+}
+
+/// Registrations joined with the attendance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reconciliation {
+    pub lines: Vec<Line>,
+    /// Attendees to receive a certificate, with the registered data when
+    /// there is a mismatch.
+    pub attendees: Vec<Attendee>,
+}
+
+impl Reconciliation {
+    /// Walk-ins left out of the attendees for having no identification.
+    pub fn dropped(&self) -> impl Iterator<Item = &Line> {
+        self.lines
+            .iter()
+            .filter(|line| line.status == Status::WalkIn && line.id.is_none())
+    }
+}
+
+/// Join registrations and attendance by identification or, failing that, by
+/// email.
+pub fn reconcile(registrations: &Registrations, presences: &[Presence]) -> Reconciliation {
+    let entries = registrations.entries();
+    let mut present = vec![false; entries.len()];
+    let mut lines = Vec::new();
+    let mut attendees = Vec::new();
+
+    for presence in presences {
+        let by_id = entries
+            .iter()
+            .position(|reg| presence.id.as_ref() == Some(&reg.id));
+        let by_email = || {
+            entries
+                .iter()
+                .position(|reg| !presence.email.is_empty() && reg.email == presence.email)
+        };
+        let found = by_id.or_else(by_email);
+        let status = match found.map(|i| &entries[i]) {
+            None => Status::WalkIn,
+            Some(reg) if presence.id.as_ref().is_some_and(|id| *id != reg.id) => {
+                Status::IdMismatch {
+                    registered: reg.id.clone(),
+                }
+            }
+            Some(reg) if name::similarity(&reg.name, &presence.name) < name::MIN_SIMILARITY => {
+                Status::NameMismatch {
+                    registered: reg.name.clone(),
+                }
+            }
+            Some(_) => Status::Ok,
+        };
+
+        match found {
+            Some(i) => {
+                present[i] = true;
+                let reg: &Registration = &entries[i];
+                attendees.push(Attendee {
+                    name: reg.name.clone(),
+                    id: reg.id.clone(),
+                    workload: presence.workload,
+                    role: match presence.role {
+                        Role::Participant => reg.role,
+                        role => role,
+                    },
+                });
+            }
+            None => {
+                if let Some(id) = &presence.id {
+                    attendees.push(Attendee {
+                        name: presence.name.clone(),
+                        id: id.clone(),
+                        workload: presence.workload,
+                        role: presence.role,
+                    });
+                }
+            }
+        }
+        lines.push(Line {
+            status,
+            name: presence.name.clone(),
+            id: presence.id.clone(),
+            email: presence.email.clone(),
+            workload: Some(presence.workload),
+        });
+    }
+
+    for (reg, _) in entries.iter().zip(present).filter(|(_, present)| !present) {
+        lines.push(Line {
+            status: Status::NoShow,
+            name: reg.name.clone(),
+            id: Some(reg.id.clone()),
+            email: reg.email.clone(),
+            workload: None,
+        });
+    }
+    Reconciliation { lines, attendees }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconcile_attendance() {
+        let regs = "NOME,EMAIL,CPF\n\
+                    Ana Silva,ana@mail.com,762.050.858-95\n\
+                    Bia Souza,bia@mail.com,754.751.875-33\n\
+                    Caio Lima,caio@mail.com,681.165.785-53\n\
+                    Davi Rocha,davi@mail.com,647.748.630-09\n";
+        let regs = Registrations::read(regs.as_bytes()).expect("valid registrations");
+        let sheet = "NOME,CPF,EMAIL,CH\n\
+                     ana silva,76205085895,,2\n\
+                     Beatriz,,BIA@mail.com,2\n\
+                     Caio Lima,207.062.844-29,caio@mail.com,1h30\n\
+                     Eva,,eva@mail.com,2\n\
+                     Fábio,633.834.740-89,,2\n";
+        let presences = read_presences(sheet.as_bytes()).expect("valid attendance");
+        let result = reconcile(&regs, &presences);
+
+        let statuses = result
+            .lines
+            .iter()
+            .map(|line| (line.status.label(), line.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("ok", "Ana Silva"),
+                ("name mismatch", "Beatriz"),
+                ("cpf mismatch", "Caio Lima"),
+                ("walk-in", "Eva"),
+                ("walk-in", "Fábio"),
+                ("no-show", "Davi Rocha"),
+            ],
+            statuses
+        );
+        assert_eq!("registered as Bia Souza", result.lines[1].status.detail());
+        assert_eq!(None, result.lines[5].workload);
+
+        let attendees = result
+            .attendees
+            .iter()
+            .map(|att| (att.name.as_str(), att.id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("Ana Silva", "762.050.858-95"),
+                ("Bia Souza", "754.751.875-33"),
+                ("Caio Lima", "681.165.785-53"),
+                ("Fábio", "633.834.740-89"),
+            ],
+            attendees
+        );
+        assert_eq!(Workload::from_minutes(90), result.attendees[2].workload);
+        let dropped = result.dropped().map(|line| line.name.as_str());
+        assert_eq!(vec!["Eva"], dropped.collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,95 @@
+use super::{code::Code, csv_data::Identification};
+use crate::sql::{quote, QueryPool, Schema, ToSQL};
+
+/// Participation whose certificate is revoked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Revoked {
+    /// Certificate with a verification code, also found in the ledger.
+    Code(Code),
+    /// Participation of an attendee in the event with the given database id.
+    Attendee { id: Identification, event: u32 },
+}
+
+/// Revocation of the certificate of an attendee in an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revocation {
+    pub revoked: Revoked,
+    /// Why the certificate is revoked, written as a comment of the script.
+    pub reason: String,
+    /// Flag the participation in the `participacao.revogado` column instead
+    /// of deleting it.
+    pub flag: bool,
+}
+
+impl Revocation {
+    /// The reason as an SQL comment, on a single line.
+    pub fn comment(&self) -> String {
+        let reason = self.reason.split_whitespace().collect::<Vec<_>>();
+        format!("-- Revoked: {}\n", reason.join(" "))
+    }
+}
+
+impl ToSQL for Revocation {
+    fn to_sql_with(&self, schema: &Schema) -> QueryPool {
+        let participacao = schema.table("participacao");
+        let [p_user, p_event, codigo, revogado] =
+            schema.columns("participacao", ["usuario", "evento", "codigo", "revogado"]);
+        let condition = match &self.revoked {
+            Revoked::Code(code) => format!("{codigo}={}", quote(code.as_str())),
+            Revoked::Attendee { id, event } => {
+                let usuario = schema.table("usuario");
+                let [user_id, identificacao] = schema.columns("usuario", ["id", "identificacao"]);
+                format!(
+                    "{p_user}=(SELECT {user_id} FROM {usuario} WHERE {identificacao}={}) \
+                    AND {p_event}={event}",
+                    quote(id.as_str()),
+                )
+            }
+        };
+        let query = if self.flag {
+            format!("UPDATE {participacao} SET {revogado}=1 WHERE {condition}")
+        } else {
+            format!("DELETE FROM {participacao} WHERE {condition}")
+        };
+        QueryPool::new().query(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::csv_data::Cpf;
+
+    #[test]
+    fn revocation_to_sql() {
+        let mut revocation = Revocation {
+            revoked: Revoked::Attendee {
+                id: Cpf::new("754.751.875-33".to_owned())
+                    .expect("valid cpf")
+                    .into(),
+                event: 12,
+            },
+            reason: "Presença\nfalsa".to_owned(),
+            flag: false,
+        };
+        let condition =
+            "usuario=(SELECT id FROM usuario WHERE identificacao='754.751.875-33') AND evento=12";
+        assert_eq!(
+            format!("DELETE FROM participacao WHERE {condition};\n"),
+            revocation.to_sql().to_string()
+        );
+        assert_eq!("-- Revoked: Presença falsa\n", revocation.comment());
+        revocation.flag = true;
+        assert_eq!(
+            format!("UPDATE participacao SET revogado=1 WHERE {condition};\n"),
+            revocation.to_sql().to_string()
+        );
+
+        let code = "WIXT-JH7G-FGZF-K4IY".parse::<Code>().expect("valid code");
+        revocation.revoked = Revoked::Code(code);
+        assert_eq!(
+            "UPDATE participacao SET revogado=1 WHERE codigo='WIXT-JH7G-FGZF-K4IY';\n",
+            revocation.to_sql().to_string()
+        );
+    }
+}
@@ -0,0 +1,268 @@
+/// Placeholders understood by the certificate website.
+pub const KNOWN_PLACEHOLDERS: &[&str] = &[
+    "nome",
+    "identificacao",
+    "evento",
+    "cargaHoraria",
+    "data",
+    "codigo",
+];
+
+/// Placeholders that every certificate text must contain.
+pub const REQUIRED_PLACEHOLDERS: &[&str] = &["nome"];
+
+#[derive(Debug, PartialEq)]
+pub enum TextError {
+    /// A `#` before a known placeholder name without its closing `#`.
+    Unbalanced(String),
+    /// A placeholder that is not in the known list.
+    Unknown {
+        found: String,
+        suggestion: Option<String>,
+    },
+    /// A required placeholder that does not appear in the text.
+    Missing(String),
+}
+
+impl std::fmt::Display for TextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unbalanced(txt) => write!(f, "unbalanced '#' marker in '{txt}'"),
+            Self::Unknown {
+                found,
+                suggestion: Some(known),
+            } => write!(
+                f,
+                "unknown placeholder '#{found}#', did you mean '#{known}#'?"
+            ),
+            Self::Unknown { found, .. } => write!(f, "unknown placeholder '#{found}#'"),
+            Self::Missing(name) => write!(f, "missing required placeholder '#{name}#'"),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+/// Whether `name` can be a placeholder: letters, digits and `_`, starting
+/// with a letter.
+pub fn is_name(name: &str) -> bool {
+    name.starts_with(char::is_alphabetic) && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// A `#` of a text followed by a placeholder name.
+#[derive(Debug, PartialEq)]
+enum Marker<'a> {
+    /// A `#name#` placeholder.
+    Closed(&'a str),
+    /// A `#name` without the closing `#`.
+    Unclosed(&'a str),
+}
+
+/// The `#` markers of `text` followed by a name, where any other `#` is
+/// literal, like in `C#`.
+fn markers(text: &str) -> Vec<Marker<'_>> {
+    let mut markers = Vec::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find('#') {
+        rest = &rest[pos + 1..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let name = &rest[..len];
+        if !is_name(name) {
+            continue;
+        }
+        rest = &rest[len..];
+        match rest.strip_prefix('#') {
+            Some(after) => {
+                markers.push(Marker::Closed(name));
+                rest = after;
+            }
+            None => markers.push(Marker::Unclosed(name)),
+        }
+    }
+    markers
+}
+
+/// Set of placeholders accepted in a certificate text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholders {
+    known: Vec<String>,
+    required: Vec<String>,
+}
+
+impl Default for Placeholders {
+    fn default() -> Self {
+        Self {
+            known: KNOWN_PLACEHOLDERS.iter().map(|&s| s.to_owned()).collect(),
+            required: REQUIRED_PLACEHOLDERS
+                .iter()
+                .map(|&s| s.to_owned())
+                .collect(),
+        }
+    }
+}
+
+impl Placeholders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the known placeholders.
+    pub fn known<P>(mut self, placeholders: P) -> Self
+    where
+        P: IntoIterator<Item = String>,
+    {
+        self.known = placeholders.into_iter().collect();
+        self
+    }
+
+    /// Replace the required placeholders.
+    pub fn required<P>(mut self, placeholders: P) -> Self
+    where
+        P: IntoIterator<Item = String>,
+    {
+        self.required = placeholders.into_iter().collect();
+        self
+    }
+
+    /// Accept an extra placeholder.
+    pub fn add(&mut self, placeholder: String) {
+        self.known.push(placeholder);
+    }
+
+    fn is_known(&self, name: &str) -> bool {
+        self.known.iter().any(|known| known == name)
+    }
+
+    /// Check that no known placeholder of `text` misses its closing `#`.
+    pub fn balanced(&self, text: &str) -> Result<(), TextError> {
+        let unclosed = markers(text)
+            .into_iter()
+            .any(|marker| matches!(marker, Marker::Unclosed(name) if self.is_known(name)));
+        if unclosed {
+            return Err(TextError::Unbalanced(text.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Check the placeholders of `text` returning the ones found in order.
+    ///
+    /// Only `#name#` is a placeholder, so a `#` not followed by a name, as in
+    /// `C#`, is kept as is.
+    pub fn validate<'a>(&self, text: &'a str) -> Result<Vec<&'a str>, TextError> {
+        self.balanced(text)?;
+        let found = markers(text)
+            .into_iter()
+            .filter_map(|marker| match marker {
+                Marker::Closed(name) => Some(name),
+                Marker::Unclosed(_) => None,
+            })
+            .collect::<Vec<_>>();
+        for name in &found {
+            if !self.is_known(name) {
+                let suggestion = self
+                    .known
+                    .iter()
+                    .find(|known| known.eq_ignore_ascii_case(name))
+                    .cloned();
+                return Err(TextError::Unknown {
+                    found: (*name).to_owned(),
+                    suggestion,
+                });
+            }
+        }
+
+        if let Some(missing) = self
+            .required
+            .iter()
+            .find(|req| !found.contains(&req.as_str()))
+        {
+            return Err(TextError::Missing(missing.to_owned()));
+        }
+        Ok(found)
+    }
+}
+
+/// Replace each `#name#` placeholder of `text` by its value.
+pub fn fill<'a, V>(text: &str, values: V) -> String
+where
+    V: IntoIterator<Item = (&'a str, String)>,
+{
+    values
+        .into_iter()
+        .fold(text.to_owned(), |txt, (name, value)| {
+            txt.replace(&format!("#{name}#"), &value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_text() {
+        let txt = "Certificamos que #nome# participou do evento #evento# no #data#.";
+        let found = Placeholders::new().validate(txt).expect("valid text");
+        assert_eq!(vec!["nome", "evento", "data"], found);
+    }
+
+    #[test]
+    fn invalid_text() {
+        let placeholders = Placeholders::new();
+        assert_eq!(
+            Err(TextError::Unbalanced("#nome de #evento#".to_owned())),
+            placeholders.validate("#nome de #evento#")
+        );
+        assert!(matches!(
+            placeholders.validate("#nome# de #nome #evento#"),
+            Err(TextError::Unbalanced(_))
+        ));
+        assert_eq!(
+            Err(TextError::Unknown {
+                found: "cargahoraria".to_owned(),
+                suggestion: Some("cargaHoraria".to_owned())
+            }),
+            placeholders.validate("#nome# com #cargahoraria# hora(s)")
+        );
+        assert_eq!(
+            Err(TextError::Missing("nome".to_owned())),
+            placeholders.validate("Participou do evento #evento#")
+        );
+    }
+
+    #[test]
+    fn literal_markers() {
+        let placeholders = Placeholders::new();
+        assert_eq!(
+            Ok(vec!["nome"]),
+            placeholders.validate("#nome# fez o minicurso de C# e F#, nota #1")
+        );
+        assert_eq!(Ok(vec!["nome"]), placeholders.validate("#nome#: C#"));
+        assert!(placeholders.validate("#nome# da turma #A").is_ok());
+        assert!(placeholders.validate("#nome# no #evento").is_err());
+        assert_eq!(
+            "A fez C#",
+            fill("#nome# fez C#", [("nome", "A".to_owned())])
+        );
+    }
+
+    #[test]
+    fn custom_placeholders() {
+        let mut placeholders = Placeholders::new().required(["evento".to_owned()]);
+        assert!(placeholders.validate("#evento# por #palestrante#").is_err());
+
+        placeholders.add("palestrante".to_owned());
+        assert!(placeholders.validate("#evento# por #palestrante#").is_ok());
+
+        let placeholders = Placeholders::new().known(["nome".to_owned()]);
+        assert!(placeholders.validate("#nome# em #data#").is_err());
+    }
+
+    #[test]
+    fn fill_text() {
+        let txt = "#nome# participou do #evento# (#nome#)";
+        let values = [("nome", "A".to_owned()), ("evento", "Event".to_owned())];
+        assert_eq!("A participou do Event (A)", fill(txt, values));
+    }
+}
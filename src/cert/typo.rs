@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{csv_data::Cpf, name};
+
+/// CPF of a row of the attendees sheet, as typed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetRow {
+    /// Line of the row in the sheet file.
+    pub line: u64,
+    pub name: String,
+    pub cpf: String,
+}
+
+/// Local cache of the users already registered in the database.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Users {
+    names: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    #[serde(rename = "NOME")]
+    name: String,
+    #[serde(rename = "CPF")]
+    cpf: String,
+}
+
+impl Users {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read users from a CSV with the `NOME` and `CPF` columns, skipping the
+    /// ones without a valid CPF.
+    pub fn read<R>(src: R) -> csv::Result<Self>
+    where
+        R: std::io::Read,
+    {
+        let mut names = HashMap::new();
+        for user in csv::Reader::from_reader(src).deserialize() {
+            let user: User = user?;
+            if let Ok(cpf) = Cpf::new(user.cpf) {
+                names.insert(cpf.as_str().to_owned(), user.name);
+            }
+        }
+        Ok(Self { names })
+    }
+
+    /// Name of the user with the given CPF.
+    pub fn name(&self, cpf: &Cpf) -> Option<&str> {
+        self.names.get(cpf.as_str()).map(String::as_str)
+    }
+}
+
+/// Where a suggested CPF was also found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Match {
+    /// Another row of the sheet.
+    Row { line: u64, name: String },
+    /// A registered user.
+    User(String),
+}
+
+impl Match {
+    /// Name of the attendee or user the CPF belongs to.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Row { name, .. } | Self::User(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for Match {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Row { line, name } => write!(f, "line {line} ({name})"),
+            Self::User(name) => write!(f, "registered user {name}"),
+        }
+    }
+}
+
+/// A valid CPF that may be the one the attendee meant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub cpf: Cpf,
+    pub matches: Vec<Match>,
+}
+
+/// Suggested corrections of an invalid CPF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnosis {
+    pub row: SheetRow,
+    /// Candidates found elsewhere come first.
+    pub candidates: Vec<Candidate>,
+}
+
+impl Diagnosis {
+    /// The correction to accept without asking: the only candidate found
+    /// elsewhere, and only under a name like the attendee's. Any other
+    /// candidate has to be confirmed.
+    pub fn best(&self) -> Option<&Cpf> {
+        let mut found = self.candidates.iter().filter(|c| !c.matches.is_empty());
+        match (found.next(), found.next()) {
+            (Some(candidate), None)
+                if candidate.matches.iter().any(|found| {
+                    name::similarity(found.name(), &self.row.name) >= name::MIN_SIMILARITY
+                }) =>
+            {
+                Some(&candidate.cpf)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn fix(&self) -> Fix {
+        Fix {
+            line: self.row.line,
+            name: self.row.name.clone(),
+            cpf: self.row.cpf.clone(),
+            suggestions: self
+                .candidates
+                .iter()
+                .map(|candidate| candidate.cpf.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            correction: self
+                .best()
+                .map(|cpf| cpf.as_str().to_owned())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Suggest corrections for the invalid CPFs of `rows`, cross-checking them
+/// against the valid CPFs of the other rows and the registered `users`.
+pub fn diagnose(rows: &[SheetRow], users: &Users) -> Vec<Diagnosis> {
+    let valid = rows
+        .iter()
+        .filter_map(|row| Some((Cpf::new(row.cpf.clone()).ok()?, row)))
+        .collect::<Vec<_>>();
+    rows.iter()
+        .filter(|row| Cpf::new(row.cpf.clone()).is_err())
+        .map(|row| {
+            let mut candidates = Cpf::suggestions(&row.cpf)
+                .into_iter()
+                .map(|cpf| {
+                    let mut matches = valid
+                        .iter()
+                        .filter(|(other, _)| other == &cpf)
+                        .map(|(_, other)| Match::Row {
+                            line: other.line,
+                            name: other.name.clone(),
+                        })
+                        .collect::<Vec<_>>();
+                    if let Some(name) = users.name(&cpf) {
+                        matches.push(Match::User(name.to_owned()));
+                    }
+                    Candidate { cpf, matches }
+                })
+                .collect::<Vec<_>>();
+            candidates.sort_by_key(|candidate| candidate.matches.is_empty());
+            Diagnosis {
+                row: row.clone(),
+                candidates,
+            }
+        })
+        .collect()
+}
+
+/// A row of the CPF fixes file.
+///
+/// The file is written with the suggestions and the `CORRECAO` column filled
+/// when there is a clear best candidate, so it can be reviewed and applied.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Fix {
+    #[serde(rename = "LINHA")]
+    pub line: u64,
+    #[serde(rename = "NOME")]
+    pub name: String,
+    #[serde(rename = "CPF")]
+    pub cpf: String,
+    #[serde(rename = "SUGESTOES")]
+    pub suggestions: String,
+    #[serde(rename = "CORRECAO")]
+    pub correction: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(line: u64, name: &str, cpf: &str) -> SheetRow {
+        SheetRow {
+            line,
+            name: name.to_owned(),
+            cpf: cpf.to_owned(),
+        }
+    }
+
+    #[test]
+    fn cross_check() {
+        let rows = vec![
+            row(2, "A", "681.165.785-54"),
+            row(3, "B", "762.050.858-95"),
+            row(4, "A", "681.165.785-53"),
+        ];
+        let diagnoses = diagnose(&rows, &Users::new());
+
+        assert_eq!(1, diagnoses.len());
+        let diagnosis = &diagnoses[0];
+        assert_eq!(2, diagnosis.row.line);
+        assert_eq!("681.165.785-53", diagnosis.candidates[0].cpf.as_str());
+        assert_eq!(
+            vec![Match::Row {
+                line: 4,
+                name: "A".to_owned()
+            }],
+            diagnosis.candidates[0].matches
+        );
+        assert_eq!(Some(&diagnosis.candidates[0].cpf), diagnosis.best());
+        assert_eq!("681.165.785-53", diagnosis.fix().correction);
+    }
+
+    #[test]
+    fn registered_users() {
+        let users =
+            Users::read("NOME,CPF\nA,68116578553\nB,invalid\n".as_bytes()).expect("valid users");
+        let rows = vec![row(2, "A", "681.165.758-53")];
+        let diagnoses = diagnose(&rows, &users);
+
+        assert_eq!(
+            vec![Match::User("A".to_owned())],
+            diagnoses[0].candidates[0].matches
+        );
+        assert_eq!(Some("681.165.785-53"), diagnoses[0].best().map(Cpf::as_str));
+    }
+
+    #[test]
+    fn unconfirmed_candidate() {
+        let rows = vec![
+            row(2, "Ana Silva", "681.165.785-54"),
+            row(3, "Caio Lima", "681.165.785-53"),
+        ];
+        let diagnoses = diagnose(&rows, &Users::new());
+        assert_eq!(1, diagnoses[0].candidates[0].matches.len());
+        assert_eq!(None, diagnoses[0].best());
+
+        let users =
+            Users::read("NOME,CPF\nANA SILVA,68116578553\n".as_bytes()).expect("valid users");
+        let diagnoses = diagnose(&rows[..1], &users);
+        assert_eq!(Some("681.165.785-53"), diagnoses[0].best().map(Cpf::as_str));
+    }
+
+    #[test]
+    fn no_best_candidate() {
+        let diagnoses = diagnose(&[row(2, "A", "681.166.023-10")], &Users::new());
+        let diagnosis = &diagnoses[0];
+
+        assert_eq!(3, diagnosis.candidates.len());
+        assert_eq!(None, diagnosis.best());
+        assert!(diagnosis.fix().correction.is_empty());
+        assert_eq!(
+            "681.156.023-10 681.616.023-10 681.160.623-10",
+            diagnosis.fix().suggestions
+        );
+    }
+}
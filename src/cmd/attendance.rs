@@ -0,0 +1,71 @@
+use pet_scroll::cert::attendance::{Attendance, DEFAULT_MIN_ATTENDANCE};
+use std::path::PathBuf;
+
+use crate::{existing_file, write_attendees};
+
+#[derive(clap::Args, Debug)]
+#[command(group = clap::ArgGroup::new("sheets").required(true).multiple(true))]
+pub struct Args {
+    /// Sessions CSV file with the SESSAO and DURACAO columns.
+    #[arg(short, long, value_parser = existing_file)]
+    sessions: PathBuf,
+    /// Presence CSV file with a column of presence marks per session.
+    #[arg(short, long, value_parser = existing_file, group = "sheets")]
+    presence: Vec<PathBuf>,
+    /// Presence CSV file of a single session as "session=file", listing only
+    /// the attendees present.
+    #[arg(long = "session-sheet", value_parser = session_sheet, group = "sheets")]
+    session_sheets: Vec<(String, PathBuf)>,
+    /// Minimum percentage of the event attended to get a certificate.
+    #[arg(short, long, default_value_t = DEFAULT_MIN_ATTENDANCE, value_parser = clap::value_parser!(u32).range(1..=100))]
+    min_attendance: u32,
+    /// Attendees CSV output file.
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+fn session_sheet(s: &str) -> Result<(String, PathBuf), String> {
+    let (session, path) = s
+        .split_once('=')
+        .ok_or_else(|| "expected 'session=file'".to_owned())?;
+    Ok((session.to_owned(), existing_file(path)?))
+}
+
+pub fn run(args: Args) -> std::io::Result<()> {
+    let file = std::fs::File::open(&args.sessions)?;
+    let mut attendance =
+        Attendance::read_sessions(file).expect("Error while parsing the sessions file");
+    for path in &args.presence {
+        let file = std::fs::File::open(path)?;
+        if let Err(err) = attendance.read_sheet(file) {
+            panic!("Error while parsing {}: {err}", path.display());
+        }
+    }
+    for (session, path) in &args.session_sheets {
+        let file = std::fs::File::open(path)?;
+        if let Err(err) = attendance.read_session(session, file) {
+            panic!("Error while parsing {}: {err}", path.display());
+        }
+    }
+
+    let (eligible, ineligible) = attendance.attendees(args.min_attendance);
+    if !ineligible.is_empty() {
+        println!(
+            "{} attendees below {}% of {}:",
+            ineligible.len(),
+            args.min_attendance,
+            attendance.total().hm()
+        );
+    }
+    for att in &ineligible {
+        println!(
+            "    {} ({}): {} ({}%)",
+            att.name,
+            att.id.as_str(),
+            att.attended.hm(),
+            att.percent
+        );
+    }
+
+    write_attendees(&args.output, &eligible)
+}
@@ -0,0 +1,214 @@
+use pet_scroll::cert::{
+    csv_data::{Cpf, IdKind},
+    typo::{self, Diagnosis, Fix, SheetRow, Users},
+};
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::existing_file;
+
+#[derive(clap::Args, Debug)]
+#[command(group = clap::ArgGroup::new("mode"))]
+pub struct Args {
+    /// Attendees Info CSV file.
+    #[arg(short, long = "atts", value_parser = existing_file)]
+    attendees: PathBuf,
+    /// Registered users CSV file with the NOME and CPF columns.
+    #[arg(short, long, value_parser = existing_file)]
+    users: Option<PathBuf>,
+    /// Write the suggested corrections into this fixes CSV file for review.
+    #[arg(long, group = "mode")]
+    fixes: Option<PathBuf>,
+    /// Apply the corrections of a reviewed fixes CSV file.
+    #[arg(long, value_parser = existing_file, group = "mode", requires = "output")]
+    apply: Option<PathBuf>,
+    /// Ask which correction to accept for each invalid CPF.
+    #[arg(short, long, group = "mode", requires = "output")]
+    interactive: bool,
+    /// Corrected attendees CSV file.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> std::io::Result<()> {
+    let mut rdr = csv::Reader::from_path(&args.attendees)?;
+    let headers = rdr
+        .headers()
+        .expect("Error while parsing the attendees file")
+        .clone();
+    let col = |names: &[&str]| headers.iter().position(|col| names.contains(&col));
+    let cpf_col = col(&["CPF", "IDENTIFICACAO"]).expect("The attendees file has no CPF column");
+    let name_col = col(&["NOME"]);
+    let kind_col = col(&["TIPO_ID"]);
+
+    let records = rdr
+        .records()
+        .collect::<csv::Result<Vec<_>>>()
+        .expect("Error while parsing the attendees file");
+    let line = |record: &csv::StringRecord| record.position().map_or(0, |pos| pos.line());
+    let rows = records
+        .iter()
+        .filter(|record| {
+            let kind = kind_col.and_then(|i| record.get(i)).unwrap_or_default();
+            match IdKind::try_from(kind.to_owned()) {
+                Ok(kind) => kind == IdKind::Cpf,
+                Err(_) => {
+                    let name = name_col.and_then(|i| record.get(i)).unwrap_or_default();
+                    println!(
+                        "Line {}: {name} skipped for the invalid TIPO_ID '{kind}'",
+                        line(record)
+                    );
+                    false
+                }
+            }
+        })
+        .map(|record| SheetRow {
+            line: line(record),
+            name: name_col
+                .and_then(|i| record.get(i))
+                .unwrap_or_default()
+                .to_owned(),
+            cpf: record.get(cpf_col).unwrap_or_default().to_owned(),
+        })
+        .collect::<Vec<_>>();
+
+    let users = match &args.users {
+        Some(path) => {
+            Users::read(std::fs::File::open(path)?).expect("Error while parsing the users file")
+        }
+        None => Users::new(),
+    };
+    let diagnoses = typo::diagnose(&rows, &users);
+    if diagnoses.is_empty() {
+        println!("All CPFs are valid");
+    }
+    for diagnosis in &diagnoses {
+        report(diagnosis);
+    }
+
+    if let Some(path) = &args.fixes {
+        println!("Saving suggested corrections at {}", path.display());
+        let mut wtr = csv::Writer::from_path(path)?;
+        for diagnosis in &diagnoses {
+            wtr.serialize(diagnosis.fix())
+                .expect("failed to write into fixes file");
+        }
+        wtr.flush()?;
+    }
+
+    let corrections = if let Some(path) = &args.apply {
+        read_fixes(path)?
+    } else if args.interactive {
+        ask(&diagnoses)?
+    } else {
+        HashMap::new()
+    };
+
+    if let Some(path) = &args.output {
+        println!(
+            "Saving {} corrections at {}",
+            corrections.len(),
+            path.display()
+        );
+        let mut wtr = csv::Writer::from_path(path)?;
+        wtr.write_record(&headers)?;
+        for record in &records {
+            let fixed = record
+                .iter()
+                .enumerate()
+                .map(|(i, field)| match corrections.get(&line(record)) {
+                    Some(cpf) if i == cpf_col => cpf.as_str(),
+                    _ => field,
+                })
+                .collect::<csv::StringRecord>();
+            wtr.write_record(&fixed)?;
+        }
+        wtr.flush()?;
+    }
+    Ok(())
+}
+
+fn report(diagnosis: &Diagnosis) {
+    let row = &diagnosis.row;
+    println!(
+        "Line {}: {} has the invalid CPF '{}'",
+        row.line, row.name, row.cpf
+    );
+    if diagnosis.candidates.is_empty() {
+        println!("    no suggestions");
+    }
+    for (i, candidate) in diagnosis.candidates.iter().enumerate() {
+        let found = candidate
+            .matches
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if found.is_empty() {
+            println!("    {}) {}", i + 1, candidate.cpf.as_str());
+        } else {
+            println!(
+                "    {}) {} (matches {})",
+                i + 1,
+                candidate.cpf.as_str(),
+                found.join(", ")
+            );
+        }
+    }
+}
+
+/// Corrections of a reviewed fixes file by sheet line.
+fn read_fixes(path: &Path) -> std::io::Result<HashMap<u64, Cpf>> {
+    let mut corrections = HashMap::new();
+    for fix in csv::Reader::from_path(path)?.deserialize() {
+        let fix: Fix = fix.expect("Error while parsing the fixes file");
+        if fix.correction.trim().is_empty() {
+            continue;
+        }
+        let cpf = Cpf::new(fix.correction)
+            .unwrap_or_else(|err| panic!("Invalid correction at line {}: {err}", fix.line));
+        corrections.insert(fix.line, cpf);
+    }
+    Ok(corrections)
+}
+
+/// Ask which correction to accept for each diagnosis.
+fn ask(diagnoses: &[Diagnosis]) -> std::io::Result<HashMap<u64, Cpf>> {
+    let mut corrections = HashMap::new();
+    let mut lines = std::io::stdin().lock().lines();
+    for diagnosis in diagnoses {
+        let row = &diagnosis.row;
+        loop {
+            print!(
+                "Correction for line {} [1-{}, a CPF or empty to skip]: ",
+                row.line,
+                diagnosis.candidates.len()
+            );
+            std::io::stdout().flush()?;
+            let answer = match lines.next() {
+                Some(answer) => answer?,
+                None => return Ok(corrections),
+            };
+            let answer = answer.trim();
+            if answer.is_empty() {
+                break;
+            }
+            let cpf = match answer.parse::<usize>() {
+                Ok(i) if (1..=diagnosis.candidates.len()).contains(&i) => {
+                    Ok(diagnosis.candidates[i - 1].cpf.clone())
+                }
+                _ => Cpf::new(answer.to_owned()).map_err(|err| err.to_string()),
+            };
+            match cpf {
+                Ok(cpf) => {
+                    corrections.insert(row.line, cpf);
+                    break;
+                }
+                Err(err) => println!("    {err}"),
+            }
+        }
+    }
+    Ok(corrections)
+}
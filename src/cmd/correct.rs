@@ -0,0 +1,41 @@
+use pet_scroll::{cert::correction, config::Profile, sql::ToSQL};
+use std::path::PathBuf;
+
+use crate::{existing_file, input_hash, open_history};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Corrections CSV file, with a row per attendee and event.
+    #[arg(short, long, value_parser = existing_file)]
+    corrections: PathBuf,
+    /// SQL output file.
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+pub fn run(args: Args, profile: &Profile) -> std::io::Result<()> {
+    let schema = &profile.schema;
+    let file = std::fs::File::open(&args.corrections)?;
+    let corrections = correction::read_corrections(file)
+        .unwrap_or_else(|err| panic!("Error while parsing the corrections file: {err}"));
+    let changes = corrections
+        .iter()
+        .map(|correction| correction.changes.len())
+        .sum::<usize>();
+    println!("{changes} changes for {} certificates", corrections.len());
+
+    let queries = corrections
+        .to_sql_with(schema)
+        .into_req(schema.database())
+        .to_string();
+    println!("Saving SQL queries at {}", args.output.display());
+    std::fs::write(&args.output, queries)?;
+
+    let input_hash = input_hash(&[&args.corrections])?;
+    let (mut history, source) = open_history(profile, input_hash, &args.output);
+    history
+        .record_corrections(&corrections, &source)
+        .expect("failed to write into the history");
+    println!("Recorded the corrections at {}", profile.history.display());
+    Ok(())
+}
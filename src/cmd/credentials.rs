@@ -0,0 +1,118 @@
+use pet_scroll::{config::Profile, credentials::Credentials};
+use std::path::{Path, PathBuf};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Encrypted credentials file, by default the one of the profile.
+    #[arg(short, long)]
+    file: Option<PathBuf>,
+    #[command(subcommand)]
+    action: Action,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Action {
+    /// Add or replace a secret of the profile, like SFTP_PWD, read from the
+    /// terminal without echo.
+    Set { name: String },
+    /// Remove a secret of the profile.
+    Remove { name: String },
+    /// List the stored secrets without their values.
+    List,
+    /// Encrypt the file with a new passphrase.
+    Passphrase,
+}
+
+/// Read a line from the terminal without echo after printing `msg`.
+fn prompt(msg: &str) -> String {
+    rpassword::prompt_password(msg).expect("Error while reading the terminal")
+}
+
+/// Passphrase of the PET_SCROLL_PASSPHRASE environment variable, or asked
+/// in the terminal.
+fn passphrase() -> String {
+    std::env::var("PET_SCROLL_PASSPHRASE").unwrap_or_else(|_| prompt("Passphrase: "))
+}
+
+/// Passphrase of a new file, asked twice in the terminal.
+fn new_passphrase() -> String {
+    if let Ok(passphrase) = std::env::var("PET_SCROLL_PASSPHRASE") {
+        return passphrase;
+    }
+    let passphrase = prompt("New passphrase: ");
+    if passphrase.is_empty() || passphrase != prompt("Repeat the passphrase: ") {
+        panic!("The passphrases are empty or different");
+    }
+    passphrase
+}
+
+/// Read the credentials file, if it exists, with its passphrase.
+pub fn load(path: &Path) -> std::io::Result<Option<(Credentials, String)>> {
+    let file = match std::fs::read(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let passphrase = passphrase();
+    let credentials = Credentials::decrypt(&file, &passphrase)
+        .unwrap_or_else(|err| panic!("Error while reading {}: {err}", path.display()));
+    Ok(Some((credentials, passphrase)))
+}
+
+fn save(path: &Path, credentials: &Credentials, passphrase: &str) -> std::io::Result<()> {
+    let file = credentials
+        .encrypt(passphrase, None)
+        .unwrap_or_else(|err| panic!("Error while saving {}: {err}", path.display()));
+    std::fs::write(path, file)
+}
+
+pub fn run(args: Args, profile: &Profile) -> std::io::Result<()> {
+    let path = args.file.as_deref().unwrap_or(&profile.credentials);
+    let profile = profile.name.as_str();
+    let loaded = load(path)?;
+    let exists = loaded.is_some();
+    let (mut credentials, passphrase) = match loaded {
+        Some(loaded) => loaded,
+        None => (Credentials::new(), String::new()),
+    };
+    let passphrase = || {
+        if exists {
+            passphrase.clone()
+        } else {
+            new_passphrase()
+        }
+    };
+
+    match args.action {
+        Action::Set { name } => {
+            let secret = prompt(&format!("{name} of the {profile} profile: "));
+            match credentials.set(profile, &name, secret) {
+                Some(_) => println!("Replaced {name} of the {profile} profile"),
+                None => println!("Added {name} to the {profile} profile"),
+            }
+            save(path, &credentials, &passphrase())
+        }
+        Action::Remove { name } => match credentials.remove(profile, &name) {
+            Some(_) => {
+                println!("Removed {name} of the {profile} profile");
+                save(path, &credentials, &passphrase())
+            }
+            None => {
+                println!("No {name} in the {profile} profile");
+                Ok(())
+            }
+        },
+        Action::List => {
+            for (profile, name) in credentials.entries() {
+                println!("{profile}: {name}");
+            }
+            Ok(())
+        }
+        Action::Passphrase => {
+            if !exists {
+                panic!("There is no credentials file at {}", path.display());
+            }
+            save(path, &credentials, &new_passphrase())
+        }
+    }
+}
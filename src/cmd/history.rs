@@ -0,0 +1,114 @@
+use pet_scroll::{
+    cert::csv_data::Cpf,
+    history::{Amendment, History, Participation},
+};
+use std::path::{Path, PathBuf};
+
+use crate::existing_file;
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// History SQLite file, by default the one of the profile.
+    #[arg(short, long, value_parser = existing_file)]
+    file: Option<PathBuf>,
+    #[command(subcommand)]
+    action: Action,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Action {
+    /// List the generated import scripts.
+    List,
+    /// Show an import script with its attendees and their revocations and
+    /// corrections.
+    Show { id: i64 },
+    /// Show the participations of a CPF, or other identification, in every
+    /// import script, and their revocations and corrections.
+    Cpf { id: String },
+}
+
+fn participation(p: &Participation) -> String {
+    let mut line = format!(
+        "{} ({}), {}, {}, text {}",
+        p.name,
+        p.cpf,
+        p.role,
+        p.workload.hm(),
+        p.text
+    );
+    if let Some(code) = &p.code {
+        line.push_str(&format!(", code {code}"));
+    }
+    line
+}
+
+fn amendments(amendments: &[Amendment]) {
+    if !amendments.is_empty() {
+        println!("Revocations and corrections:");
+    }
+    for amendment in amendments {
+        println!("    {amendment}");
+    }
+}
+
+pub fn run(args: Args, default: &Path) -> std::io::Result<()> {
+    let path = args.file.as_deref().unwrap_or(default);
+    if !path.exists() {
+        println!("No history at {}", path.display());
+        return Ok(());
+    }
+    let history =
+        History::open(path).unwrap_or_else(|err| panic!("Error while opening the history: {err}"));
+
+    match args.action {
+        Action::List => {
+            for import in history.imports().expect("Error while reading the history") {
+                println!(
+                    "#{} {} {} ({}) -> {}",
+                    import.id, import.created, import.event, import.date, import.source.output
+                );
+            }
+        }
+        Action::Show { id } => match history.import(id).expect("Error while reading the history") {
+            Some((import, participations)) => {
+                println!("{import}");
+                println!("Attendees:");
+                for p in &participations {
+                    println!("    {}", participation(p));
+                }
+                amendments(
+                    &history
+                        .import_amendments(id)
+                        .expect("Error while reading the history"),
+                );
+            }
+            None => println!("No import #{id} at {}", path.display()),
+        },
+        Action::Cpf { id } => {
+            // CPFs are recorded formatted
+            let id = Cpf::new(id.clone()).map_or(id, |cpf| cpf.as_str().to_owned());
+            let found = history
+                .history(&id)
+                .expect("Error while reading the history");
+            if found.is_empty() {
+                println!("No participation of {id} at {}", path.display());
+            }
+            for (import, p) in found {
+                println!(
+                    "#{} {} {} ({}): {}",
+                    import.id,
+                    import.created,
+                    import.event,
+                    import.date,
+                    participation(&p)
+                );
+            }
+            amendments(
+                &history
+                    .amendments(&id)
+                    .expect("Error while reading the history"),
+            );
+        }
+    }
+    Ok(())
+}
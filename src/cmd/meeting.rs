@@ -0,0 +1,63 @@
+use pet_scroll::cert::meeting::{self, Platform, Registrations, Report};
+use std::path::PathBuf;
+
+use crate::{existing_file, write_attendees};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Platform of the reports: meet, zoom, jitsi or teams.
+    #[arg(short, long)]
+    platform: Platform,
+    /// Participants report CSV file, one per meeting.
+    #[arg(short, long, value_parser = existing_file, required = true)]
+    report: Vec<PathBuf>,
+    /// Registrations CSV file with the attendees sheet columns plus EMAIL.
+    #[arg(long, value_parser = existing_file)]
+    registrations: PathBuf,
+    /// Attendees CSV output file.
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Write the unmatched participants into this CSV file.
+    #[arg(short, long)]
+    unmatched: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> std::io::Result<()> {
+    let file = std::fs::File::open(&args.registrations)?;
+    let registrations = Registrations::read(file)
+        .unwrap_or_else(|err| panic!("Error while parsing the registrations file: {err}"));
+    let mut report = Report::new();
+    for path in &args.report {
+        let file = std::fs::File::open(path)?;
+        if let Err(err) = report.read(args.platform, file) {
+            panic!("Error while parsing {}: {err}", path.display());
+        }
+    }
+
+    let (attendees, unmatched) = meeting::import(&report, &registrations);
+    if !unmatched.is_empty() {
+        println!("{} participants without registration:", unmatched.len());
+    }
+    for participant in &unmatched {
+        println!(
+            "    {} <{}>: {}",
+            participant.name,
+            participant.email,
+            participant.duration().hm()
+        );
+    }
+    if let Some(path) = &args.unmatched {
+        println!("Saving unmatched participants at {}", path.display());
+        let mut wtr = csv::Writer::from_path(path)?;
+        wtr.write_record(["NOME", "EMAIL", "CH"])?;
+        for participant in &unmatched {
+            wtr.write_record([
+                participant.name.as_str(),
+                participant.email.as_str(),
+                &participant.duration().hm(),
+            ])?;
+        }
+        wtr.flush()?;
+    }
+    write_attendees(&args.output, &attendees)
+}
@@ -0,0 +1,10 @@
+pub mod attendance;
+pub mod check_cpf;
+pub mod correct;
+pub mod credentials;
+pub mod history;
+pub mod meeting;
+pub mod reconcile;
+pub mod render;
+pub mod revoke;
+pub mod verify;
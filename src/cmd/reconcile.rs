@@ -0,0 +1,117 @@
+use pet_scroll::cert::{
+    meeting::Registrations,
+    reconcile::{self, Line, Status},
+};
+use std::path::PathBuf;
+
+use crate::{existing_file, write_attendees};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Registrations CSV file with the attendees sheet columns plus EMAIL.
+    #[arg(long, value_parser = existing_file)]
+    registrations: PathBuf,
+    /// Attendance CSV file with the attendees sheet columns, where CPF may be
+    /// replaced by EMAIL.
+    #[arg(short, long, value_parser = existing_file)]
+    atts: PathBuf,
+    /// Attendees CSV output file.
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Write the reconciliation report into this CSV file.
+    #[arg(long)]
+    report: Option<PathBuf>,
+}
+
+const HEADERS: [&str; 6] = ["SITUACAO", "NOME", "CPF", "EMAIL", "CH", "DETALHE"];
+
+fn cells(line: &Line) -> [String; 6] {
+    [
+        line.status.label().to_owned(),
+        line.name.clone(),
+        line.id
+            .as_ref()
+            .map(|id| id.as_str().to_owned())
+            .unwrap_or_default(),
+        line.email.clone(),
+        line.workload.map(|w| w.hm()).unwrap_or_default(),
+        line.status.detail(),
+    ]
+}
+
+/// Print the rows as a table with columns padded to the widest cell.
+fn print_table(rows: &[[String; 6]]) {
+    let mut widths = HEADERS.map(|header| header.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let print_row = |row: &[&str]| {
+        let cells = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>();
+        println!("{}", cells.join(" | ").trim_end());
+    };
+    print_row(&HEADERS);
+    let rule = widths.map(|width| "-".repeat(width));
+    println!("{}", rule.join("-+-"));
+    for row in rows {
+        print_row(&row.each_ref().map(String::as_str));
+    }
+}
+
+pub fn run(args: Args) -> std::io::Result<()> {
+    let file = std::fs::File::open(&args.registrations)?;
+    let registrations = Registrations::read(file)
+        .unwrap_or_else(|err| panic!("Error while parsing the registrations file: {err}"));
+    let file = std::fs::File::open(&args.atts)?;
+    let presences = reconcile::read_presences(file)
+        .unwrap_or_else(|err| panic!("Error while parsing the attendance file: {err}"));
+
+    let result = reconcile::reconcile(&registrations, &presences);
+    let rows = result.lines.iter().map(cells).collect::<Vec<_>>();
+    print_table(&rows);
+
+    for line in &result.lines {
+        match &line.status {
+            Status::NameMismatch { registered } => {
+                println!(
+                    "Warning: {} is written with the registered name {registered}",
+                    line.name
+                )
+            }
+            Status::IdMismatch { registered } => println!(
+                "Warning: {} is written with the registered CPF {} instead of {}",
+                line.name,
+                registered.as_str(),
+                line.id.as_ref().map(|id| id.as_str()).unwrap_or_default(),
+            ),
+            _ => {}
+        }
+    }
+    let dropped = result.dropped().collect::<Vec<_>>();
+    if !dropped.is_empty() {
+        println!(
+            "Warning: {} walk-in(s) without CPF left out of {}:",
+            dropped.len(),
+            args.output.display()
+        );
+        for line in dropped {
+            println!("  {} <{}>", line.name, line.email);
+        }
+    }
+
+    if let Some(path) = &args.report {
+        println!("Saving reconciliation report at {}", path.display());
+        let mut wtr = csv::Writer::from_path(path)?;
+        wtr.write_record(HEADERS)?;
+        for row in &rows {
+            wtr.write_record(row)?;
+        }
+        wtr.flush()?;
+    }
+    write_attendees(&args.output, &result.attendees)
+}
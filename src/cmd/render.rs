@@ -0,0 +1,155 @@
+use pet_scroll::{
+    cert::{
+        csv_data::{Attendee, EventDesc, Rounding},
+        duplicate::{Dedup, NamePolicy, WorkloadPolicy},
+        text,
+    },
+    config::Profile,
+    org::Org,
+    pdf::{Background, Document, Layout, Page, QrBox, TextBox},
+};
+use std::{io::Write, path::PathBuf};
+
+use crate::{existing_file, name_case, read_attendees, read_event, round, signer};
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Event Data CSV file.
+    #[arg(short, long, value_parser = existing_file)]
+    event: PathBuf,
+    /// Attendees Info CSV file.
+    #[arg(short, long = "atts", value_parser = existing_file)]
+    attendees: PathBuf,
+    /// Keep the attendee names casing exactly as in the attendees file.
+    #[arg(long)]
+    keep_name_case: bool,
+    /// How the workloads of a repeated attendee are merged: sum, max or reject.
+    #[arg(long, default_value = "max")]
+    duplicate_hours: WorkloadPolicy,
+    /// Which name of a repeated attendee is kept: first, longest or ask.
+    #[arg(long, default_value = "first")]
+    duplicate_names: NamePolicy,
+    /// How workloads are rounded: ceil, floor, nearest or half-hour,
+    /// by default the one of the profile or ceil.
+    #[arg(long)]
+    rounding: Option<Rounding>,
+    /// Event certificate PNG image.
+    #[arg(short, long, value_parser = existing_file)]
+    img: PathBuf,
+    /// Content of the existing text id used by the event, or by a role,
+    /// required when there is one. Roles with their own text keep it.
+    #[arg(short, long)]
+    text: Option<String>,
+    /// Region of the certificate text as "x,y,width,height[,font_size]".
+    ///
+    /// Positions and sizes are in millimetres from the top left corner of
+    /// the page and the font size is in points.
+    #[arg(long)]
+    text_box: Option<TextBox>,
+    /// Write the #data# placeholder in long form, like "4 a 6 de maio de 2023".
+    #[arg(long)]
+    long_date: bool,
+    /// Issue verification codes using the CERT_SECRET environment variable.
+    #[arg(long)]
+    codes: bool,
+    /// Draw a QR code pointing to this URL followed by the verification code.
+    #[arg(long, requires = "codes")]
+    verify_url: Option<String>,
+    /// Region of the QR code as "x,y,size" in millimetres.
+    #[arg(long, requires = "verify_url")]
+    qr_box: Option<QrBox>,
+    /// Write all certificates into a single PDF file.
+    #[arg(short, long)]
+    merge: bool,
+    /// Output directory, or output PDF file when merging.
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+pub fn run(args: Args, profile: &Profile, org: Option<&Org>) -> std::io::Result<()> {
+    let evt = read_event(&args.event, profile, org)?;
+    for desc in std::iter::once(&evt.desc).chain(evt.roles.values()) {
+        if let Some(name) = desc.catalog_name() {
+            panic!("The event text @{name} needs an organization catalog, choose it with --org");
+        }
+    }
+    if let Some(text) = &args.text {
+        if let Err(err) = profile.placeholders.validate(text) {
+            panic!("Invalid certificate text: {err}");
+        }
+    }
+    let atts = read_attendees(
+        &args.attendees,
+        name_case(args.keep_name_case),
+        Dedup {
+            workload: args.duplicate_hours,
+            name: args.duplicate_names,
+        },
+    )?;
+    let mut event = evt.into_event(atts);
+    round(&mut event, args.rounding.unwrap_or(profile.rounding));
+    if args.codes {
+        event = event.sign(signer(profile)?);
+    }
+
+    let mut layout = Layout::default();
+    if let Some(body) = args.text_box {
+        layout.body = body;
+    }
+    if let Some(qr) = args.qr_box {
+        layout.qr = qr;
+    }
+
+    let page = |att: &Attendee| {
+        // the text of an existing text id is only in the database
+        let text = match (event.data().desc(att.role), &args.text) {
+            (EventDesc::Text(text), _) | (EventDesc::Id(_), Some(text)) => text,
+            (EventDesc::Id(id), None) => {
+                panic!("The event uses the existing text {id}, provide its content with --text")
+            }
+        };
+        let mut values = event.placeholders(att);
+        if args.long_date {
+            for (key, value) in &mut values {
+                if *key == "data" {
+                    *value = event.data().date.long();
+                }
+            }
+        }
+        let page = Page::new(text::fill(text, values));
+        match (&args.verify_url, event.code(att)) {
+            (Some(url), Some(code)) => page
+                .qr(&format!("{url}{code}"))
+                .expect("The verification URL is too long for a QR code"),
+            _ => page,
+        }
+    };
+
+    print!("Reading certificate image...");
+    std::io::stdout().flush()?;
+    let background = Background::open(&args.img)?;
+    println!(" Done!");
+
+    if args.merge {
+        let mut doc = Document::new(&background, &layout);
+        for att in event.attendees() {
+            doc.add(page(att));
+        }
+        println!("Saving certificates at {}", args.output.display());
+        std::fs::write(&args.output, doc.finish())?;
+    } else {
+        std::fs::create_dir_all(&args.output)?;
+        println!("Saving certificates at {}", args.output.display());
+        for att in event.attendees() {
+            let id = att
+                .id
+                .as_str()
+                .chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>();
+            let pdf = Document::new(&background, &layout).page(page(att)).finish();
+            std::fs::write(args.output.join(format!("{id}.pdf")), pdf)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use pet_scroll::{
+    cert::{
+        code::Code,
+        csv_data::IdKind,
+        ledger::Ledger,
+        revocation::{Revocation, Revoked},
+    },
+    config::Profile,
+    sql::ToSQL,
+};
+use std::path::{Path, PathBuf};
+
+use crate::{existing_file, id_kind, identification, input_hash, open_history};
+
+#[derive(clap::Args, Debug)]
+#[command(group = clap::ArgGroup::new("certificate").required(true))]
+pub struct Args {
+    /// Verification code of the certificate, which is also revoked in the
+    /// ledger.
+    #[arg(short, long, group = "certificate")]
+    code: Option<Code>,
+    /// CPF or other identification of the attendee of a certificate without
+    /// code.
+    #[arg(long, visible_alias = "cpf", group = "certificate", requires = "event")]
+    id: Option<String>,
+    /// Kind of the identification: cpf, passaporte, rne or institucional.
+    #[arg(long, default_value = "cpf", value_parser = id_kind, requires = "id")]
+    id_kind: IdKind,
+    /// Id of the event in the database.
+    #[arg(short, long, requires = "id")]
+    event: Option<u32>,
+    /// Why the certificate is revoked.
+    #[arg(short, long)]
+    reason: String,
+    /// Flag the participation in the `participacao.revogado` column instead
+    /// of deleting it.
+    #[arg(long)]
+    flag: bool,
+    /// SQL output file.
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Mark the certificate as revoked in this ledger CSV file, by default
+    /// the one of the organization.
+    #[arg(short, long, value_parser = existing_file)]
+    ledger: Option<PathBuf>,
+}
+
+pub fn run(args: Args, profile: &Profile, org_ledger: Option<&Path>) -> std::io::Result<()> {
+    let schema = &profile.schema;
+    let revoked = match (&args.code, args.id, args.event) {
+        (Some(code), _, _) => Revoked::Code(code.clone()),
+        (None, Some(id), Some(event)) => Revoked::Attendee {
+            id: identification(args.id_kind, id),
+            event,
+        },
+        _ => unreachable!("a code or an identification and event are required"),
+    };
+    let revocation = Revocation {
+        revoked,
+        reason: args.reason,
+        flag: args.flag,
+    };
+    let queries = revocation
+        .to_sql_with(schema)
+        .into_req(schema.database())
+        .to_string();
+    println!("Saving SQL queries at {}", args.output.display());
+    std::fs::write(&args.output, revocation.comment() + &queries)?;
+
+    let (mut history, source) = open_history(profile, input_hash(&[])?, &args.output);
+    let id = history
+        .record_revocation(&revocation, &source)
+        .expect("failed to write into the history");
+    println!("Recorded revocation #{id} at {}", profile.history.display());
+
+    let Some(path) = args.ledger.as_deref().or(org_ledger) else {
+        return Ok(());
+    };
+    let file = std::fs::File::open(path)?;
+    let mut ledger =
+        Ledger::read(std::io::BufReader::new(file)).expect("Error while parsing the ledger file");
+    let codes = match &revocation.revoked {
+        Revoked::Code(code) => vec![code.clone()],
+        Revoked::Attendee { id, event } => {
+            let issued = ledger.find_id(id, None);
+            let unknown = issued
+                .iter()
+                .filter(|issued| issued.event_id.is_none())
+                .collect::<Vec<_>>();
+            if !unknown.is_empty() {
+                println!(
+                    "Warning: {} certificate(s) of {} at {} have no event id, \
+                    revoke them with --code if they are of event {event}:",
+                    unknown.len(),
+                    id.as_str(),
+                    path.display()
+                );
+                for issued in unknown {
+                    println!("{issued}");
+                }
+            }
+            issued
+                .iter()
+                .filter(|issued| issued.event_id == Some(*event))
+                .map(|issued| issued.code.clone())
+                .collect()
+        }
+    };
+    if codes.is_empty() {
+        println!("No certificate to revoke at {}", path.display());
+    }
+    for code in &codes {
+        match ledger.revoke(code, &revocation.reason) {
+            Some(issued) => println!("Revoked at {}\n{issued}", path.display()),
+            None => println!("No certificate {code} at {}", path.display()),
+        }
+    }
+    ledger
+        .write(std::fs::File::create(path)?)
+        .expect("failed to write into ledger file");
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use pet_scroll::{
+    cert::{
+        code::Code,
+        csv_data::{EventDate, IdKind, Identification, Workload},
+        ledger::{Issued, Ledger},
+    },
+    config::Profile,
+};
+use std::path::{Path, PathBuf};
+
+use crate::{existing_file, id_kind, identification, signer, Secrets};
+
+#[derive(clap::Args, Debug)]
+#[command(group = clap::ArgGroup::new("query").required(true).multiple(true))]
+pub struct Args {
+    /// Verification code of the certificate.
+    #[arg(short, long, group = "query")]
+    code: Option<Code>,
+    /// CPF or other identification of the attendee.
+    #[arg(long, visible_alias = "cpf", group = "query")]
+    id: Option<String>,
+    /// Kind of the identification: cpf, passaporte, rne or institucional.
+    #[arg(long, default_value = "cpf", value_parser = id_kind, requires = "id")]
+    id_kind: IdKind,
+    /// Event name.
+    #[arg(short, long)]
+    event: Option<String>,
+    /// Issued certificates ledger CSV file, by default the one of the
+    /// organization.
+    ///
+    /// Without a ledger the code is checked against the CERT_SECRET key,
+    /// which requires the code, CPF, event and date.
+    #[arg(short, long, value_parser = existing_file)]
+    ledger: Option<PathBuf>,
+    /// Event date as written in the event file.
+    #[arg(
+        short,
+        long,
+        value_parser = |s: &str| EventDate::try_from(s.to_owned()).map_err(|err| err.to_string())
+    )]
+    date: Option<EventDate>,
+}
+
+pub fn run(args: Args, profile: &Profile, org_ledger: Option<&Path>) -> std::io::Result<()> {
+    let id = args.id.map(|id| identification(args.id_kind, id));
+    let found = match args.ledger.as_deref().or(org_ledger) {
+        Some(path) => {
+            let file = std::fs::File::open(path)?;
+            let ledger = Ledger::read(std::io::BufReader::new(file))
+                .expect("Error while parsing the ledger file");
+            lookup(&ledger, &args.code, &id, args.event.as_deref())
+        }
+        None => {
+            let (Some(code), Some(id), Some(event), Some(date)) =
+                (args.code, id, args.event, args.date)
+            else {
+                panic!("Without a ledger, --code, --id, --event and --date are required");
+            };
+            // the workload is not signed, so it is unknown here
+            let issued = Issued {
+                code,
+                event,
+                date: date.to_string(),
+                name: String::new(),
+                cpf: id.as_str().to_owned(),
+                workload: Workload::from_minutes(0),
+                revoked: None,
+                event_id: None,
+            };
+            if signer(profile)?.check(&issued) {
+                println!("Valid certificate code");
+                println!("Code:     {}", issued.code);
+                println!("Event:    {}", issued.event);
+                println!("Date:     {}", issued.date);
+                println!("CPF:      {}", issued.cpf);
+                return Ok(());
+            }
+            Vec::new()
+        }
+    };
+
+    if found.is_empty() {
+        println!("No certificate found");
+        std::process::exit(1);
+    }
+
+    // check the ledger entries when the secret key is available
+    let check = Secrets::load(profile)?.signer();
+    for issued in found {
+        match issued.revoked {
+            Some(_) => println!("Certificate REVOKED"),
+            None => println!("Certificate issued"),
+        }
+        println!("{issued}");
+        match &check {
+            Some(signer) if signer.check(&issued) => println!("Signature: valid"),
+            Some(_) => println!("Signature: INVALID, the ledger entry was tampered"),
+            None => println!("Signature: not checked, CERT_SECRET is not defined"),
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// Ledger entries matching every given filter.
+fn lookup(
+    ledger: &Ledger,
+    code: &Option<Code>,
+    id: &Option<Identification>,
+    event: Option<&str>,
+) -> Vec<Issued> {
+    let found = match (code, id) {
+        (Some(code), _) => ledger.find_code(code).into_iter().collect(),
+        (None, Some(id)) => ledger.find_id(id, event),
+        (None, None) => unreachable!("a code or identification is required"),
+    };
+    found
+        .into_iter()
+        .filter(|issued| id.as_ref().is_none_or(|id| issued.cpf == id.as_str()))
+        .filter(|issued| event.is_none_or(|event| issued.event == event))
+        .cloned()
+        .collect()
+}
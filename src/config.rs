@@ -0,0 +1,430 @@
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cert::{
+        csv_data::Rounding,
+        text::{self, Placeholders, KNOWN_PLACEHOLDERS, REQUIRED_PLACEHOLDERS},
+    },
+    sql::Schema,
+};
+
+/// Default configuration file, searched in the current directory and its
+/// parents.
+pub const DEFAULT_PATH: &str = "pet-scroll.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// Line or key of the error.
+    at: String,
+    msg: String,
+}
+
+impl ConfigError {
+    /// Error in the value of `key` in `section`.
+    pub fn invalid(section: &str, key: &str, msg: impl Into<String>) -> Self {
+        Self {
+            at: format!("[{section}] {key}"),
+            msg: msg.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.at, self.msg)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Names of a schema section by default name, like `evento` or
+/// `evento.nome`, and the `database` name.
+pub(crate) type Names = BTreeMap<String, String>;
+
+/// The TOML configuration file, with the `[schema]`, `[profiles.<name>]` and
+/// `[orgs.<name>]` sections.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Profile used without `--profile`.
+    profile: Option<String>,
+    #[serde(default)]
+    pub(crate) schema: Names,
+    #[serde(default)]
+    profiles: BTreeMap<String, Settings>,
+    #[serde(default)]
+    pub(crate) orgs: BTreeMap<String, Settings>,
+}
+
+/// Keys of a profile or organization section.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Settings {
+    img_dir: Option<String>,
+    img_url: Option<String>,
+    sftp_address: Option<String>,
+    sftp_user: Option<String>,
+    sftp_auth: Option<AuthKind>,
+    sftp_key: Option<PathBuf>,
+    rounding: Option<String>,
+    strict: Option<bool>,
+    /// Placeholders known besides the ones of the certificate website.
+    #[serde(default)]
+    placeholders: Vec<String>,
+    required_placeholders: Option<Vec<String>>,
+    pub(crate) history: Option<PathBuf>,
+    credentials: Option<PathBuf>,
+    #[serde(default)]
+    schema: Names,
+    /// Ledger of an organization.
+    pub(crate) ledger: Option<PathBuf>,
+    /// Certificate texts of an organization by name.
+    #[serde(default)]
+    pub(crate) texts: BTreeMap<String, CatalogText>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum AuthKind {
+    Password,
+    Agent,
+    Key,
+}
+
+/// A text of an organization catalog, given as the text or as the id of a
+/// registered one.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum CatalogText {
+    Text(String),
+    Id(i64),
+}
+
+impl Config {
+    pub fn parse(src: &str) -> Result<Self, ConfigError> {
+        toml::from_str(src).map_err(|err: toml::de::Error| {
+            let line = err
+                .span()
+                .map_or(1, |span| src[..span.start].matches('\n').count() + 1);
+            ConfigError {
+                at: format!("line {line}"),
+                msg: err.message().to_owned(),
+            }
+        })
+    }
+
+    /// Read the configuration file at `path`, or the default one if it
+    /// exists, returning an empty configuration otherwise.
+    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = match path {
+            Some(path) => path.to_owned(),
+            None => match find(&std::env::current_dir()?) {
+                Some(path) => path,
+                None => return Ok(Self::default()),
+            },
+        };
+        Ok(Self::parse(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// How a profile authenticates to the SFTP server.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum SftpAuth {
+    /// Password in the SFTP_PWD environment variable.
+    #[default]
+    Password,
+    /// Keys of the running SSH agent.
+    Agent,
+    /// Private key file.
+    Key(PathBuf),
+}
+
+/// Settings of a `[profiles.<name>]` section, with the database names of
+/// `[profiles.<name>.schema]` over the ones of `[schema]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// Name of the profile or organization, `default` without one.
+    pub name: String,
+    pub schema: Schema,
+    /// Remote directory of the certificate images.
+    pub img_dir: String,
+    /// Prefix of the image paths saved in the database.
+    pub img_url: String,
+    pub sftp_address: Option<String>,
+    pub sftp_user: Option<String>,
+    pub sftp_auth: SftpAuth,
+    /// Default rounding of the workloads.
+    pub rounding: Rounding,
+    /// Refuse to generate the SQL of events with warnings.
+    pub strict: bool,
+    /// Placeholders accepted in the certificate texts.
+    pub placeholders: Placeholders,
+    /// SQLite file of the history of the generated scripts.
+    pub history: PathBuf,
+    /// Encrypted file of the secrets used in the uploads.
+    pub credentials: PathBuf,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: "default".to_owned(),
+            schema: Schema::default(),
+            img_dir: "./certificados/img".to_owned(),
+            img_url: "img/".to_owned(),
+            sftp_address: None,
+            sftp_user: None,
+            sftp_auth: SftpAuth::default(),
+            rounding: Rounding::default(),
+            strict: false,
+            placeholders: Placeholders::default(),
+            history: PathBuf::from(crate::history::DEFAULT_PATH),
+            credentials: PathBuf::from(crate::credentials::DEFAULT_PATH),
+        }
+    }
+}
+
+impl Config {
+    /// Settings of the profile `name`, or of the one in the `profile` key
+    /// before any section, or the default settings if there is none.
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, ConfigError> {
+        let Some(name) = name.or(self.profile.as_deref()) else {
+            return Ok(Profile {
+                schema: Schema::from_config(self)?,
+                ..Profile::default()
+            });
+        };
+        let settings = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| ConfigError::invalid("profiles", name, "unknown profile"))?;
+        let section = format!("profiles.{name}");
+        if settings.ledger.is_some() || !settings.texts.is_empty() {
+            let key = if settings.ledger.is_some() {
+                "ledger"
+            } else {
+                "texts"
+            };
+            return Err(ConfigError::invalid(
+                &section,
+                key,
+                "only allowed in organizations",
+            ));
+        }
+        Profile::read(self, name, settings, &section)
+    }
+}
+
+impl Profile {
+    /// Settings of a section, like `profiles.prod`, with its schema over the
+    /// one of the configuration.
+    pub(crate) fn read(
+        config: &Config,
+        name: &str,
+        settings: &Settings,
+        section: &str,
+    ) -> Result<Self, ConfigError> {
+        let default = Profile::default();
+        let mut schema = Schema::from_config(config)?;
+        schema.apply(&settings.schema, &format!("{section}.schema"))?;
+        let sftp_auth = match settings.sftp_auth {
+            None | Some(AuthKind::Password) => SftpAuth::Password,
+            Some(AuthKind::Agent) => SftpAuth::Agent,
+            Some(AuthKind::Key) => SftpAuth::Key(settings.sftp_key.clone().ok_or_else(|| {
+                ConfigError::invalid(section, "sftp_key", "required by the key authentication")
+            })?),
+        };
+        let rounding = match &settings.rounding {
+            Some(rounding) => rounding
+                .parse::<Rounding>()
+                .map_err(|err| ConfigError::invalid(section, "rounding", err.to_string()))?,
+            None => default.rounding,
+        };
+        let placeholders = read_placeholders(settings, section)?;
+        Ok(Profile {
+            name: name.to_owned(),
+            schema,
+            img_dir: settings.img_dir.clone().unwrap_or(default.img_dir),
+            img_url: settings.img_url.clone().unwrap_or(default.img_url),
+            sftp_address: settings.sftp_address.clone(),
+            sftp_user: settings.sftp_user.clone(),
+            sftp_auth,
+            rounding,
+            strict: settings.strict.unwrap_or(default.strict),
+            placeholders,
+            history: settings.history.clone().unwrap_or(default.history),
+            credentials: settings.credentials.clone().unwrap_or(default.credentials),
+        })
+    }
+}
+
+/// The known placeholders with the extra ones of `placeholders`, and the
+/// `required_placeholders` among them.
+fn read_placeholders(settings: &Settings, section: &str) -> Result<Placeholders, ConfigError> {
+    for name in &settings.placeholders {
+        if !text::is_name(name) {
+            let msg = format!("invalid placeholder name '{name}'");
+            return Err(ConfigError::invalid(section, "placeholders", msg));
+        }
+    }
+    let known = KNOWN_PLACEHOLDERS
+        .iter()
+        .map(|&name| name.to_owned())
+        .chain(settings.placeholders.iter().cloned())
+        .collect::<Vec<_>>();
+    let required = match &settings.required_placeholders {
+        Some(required) => required.clone(),
+        None => REQUIRED_PLACEHOLDERS
+            .iter()
+            .map(|&name| name.to_owned())
+            .collect(),
+    };
+    if let Some(name) = required.iter().find(|name| !known.contains(name)) {
+        let msg = format!("unknown placeholder '{name}'");
+        return Err(ConfigError::invalid(section, "required_placeholders", msg));
+    }
+    Ok(Placeholders::new().known(known).required(required))
+}
+
+/// Find the configuration file in `dir` or its parents.
+fn find(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(DEFAULT_PATH))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let src = "# PET groups\n\
+                   profile = 'minicurso' # comment\n\
+                   \n\
+                   [schema]\n\
+                   database = \"pet_db\"\n\
+                   \"evento.nome\" = \"titulo\"\n\
+                   \n\
+                   [profiles.minicurso]\n\
+                   rounding = \"half-hour\"\n\
+                   schema = { database = \"\"\"pet_minicurso\"\"\" }\n\
+                   [profiles.palestra]\n\
+                   schema.usuario = \"usuarios\"\n";
+        let config = Config::parse(src).expect("valid config");
+        let minicurso = config.profile(None).expect("default profile");
+        assert_eq!("minicurso", minicurso.name);
+        assert_eq!("pet_minicurso", minicurso.schema.database());
+        assert_eq!(["titulo"], minicurso.schema.columns("evento", ["nome"]));
+        assert_eq!(Rounding::HalfHour, minicurso.rounding);
+        let palestra = config.profile(Some("palestra")).expect("profile");
+        assert_eq!("pet_db", palestra.schema.database());
+        assert_eq!("usuarios", palestra.schema.table("usuario"));
+    }
+
+    #[test]
+    fn invalid_config() {
+        for src in [
+            "[schema",
+            "[]",
+            "profile",
+            "profile = value",
+            "profile = \"unclosed",
+            "a b = 1",
+            "profile = \"a\"\nprofile = \"b\"",
+            "[schema]\n[schema]",
+            "name = \"PETComp\"",
+            "[profiles.a]\nstrict = \"yes\"",
+        ] {
+            assert!(Config::parse(src).is_err(), "{src}");
+        }
+        let err = Config::parse("profile = \"a\"\n\nb = c").expect_err("invalid value");
+        assert!(err.to_string().starts_with("line 3: "), "{err}");
+    }
+
+    #[test]
+    fn profile_config() {
+        let src = "profile = \"prod\"\n\
+                   [schema]\n\
+                   evento = \"eventos\"\n\
+                   [profiles.prod]\n\
+                   img_url = \"https://pet.example/img/\"\n\
+                   sftp_address = \"pet.example:22\"\n\
+                   sftp_auth = \"key\"\n\
+                   sftp_key = \"/home/pet/.ssh/id_ed25519\"\n\
+                   rounding = \"half-hour\"\n\
+                   strict = true\n\
+                   credentials = \"/home/pet/prod.age\"\n\
+                   [profiles.prod.schema]\n\
+                   database = \"pet_prod\"\n\
+                   [profiles.staging]\n\
+                   img_dir = \"./staging/img\"\n";
+        let config = Config::parse(src).expect("valid config");
+
+        let prod = config.profile(None).expect("default profile");
+        assert_eq!(prod, config.profile(Some("prod")).expect("prod profile"));
+        assert_eq!("prod", prod.name);
+        assert_eq!("pet_prod", prod.schema.database());
+        assert_eq!("eventos", prod.schema.table("evento"));
+        assert_eq!("https://pet.example/img/", prod.img_url);
+        assert_eq!("./certificados/img", prod.img_dir);
+        assert_eq!(Some("pet.example:22"), prod.sftp_address.as_deref());
+        assert_eq!(
+            SftpAuth::Key(PathBuf::from("/home/pet/.ssh/id_ed25519")),
+            prod.sftp_auth
+        );
+        assert_eq!(Rounding::HalfHour, prod.rounding);
+        assert!(prod.strict);
+        assert_eq!(PathBuf::from("/home/pet/prod.age"), prod.credentials);
+        assert_eq!(Placeholders::default(), prod.placeholders);
+
+        let staging = config.profile(Some("staging")).expect("staging profile");
+        assert_eq!("petcomp", staging.schema.database());
+        assert_eq!("./staging/img", staging.img_dir);
+        assert_eq!("img/", staging.img_url);
+        assert_eq!(Rounding::Ceil, staging.rounding);
+        assert_eq!(PathBuf::from("pet-scroll.age"), staging.credentials);
+        assert_eq!(Placeholders::default(), staging.placeholders);
+
+        assert_eq!(
+            "[profiles] other: unknown profile",
+            config.profile(Some("other")).unwrap_err().to_string()
+        );
+        for src in [
+            "[profiles.a]\nrounding = \"up\"",
+            "[profiles.a]\nstrict = \"yes\"",
+            "[profiles.a]\nsftp_auth = \"key\"",
+            "[profiles.a]\nsftp_auth = \"token\"",
+            "[profiles.a]\nhost = \"pet.example\"",
+            "[profiles.a]\nledger = \"a.csv\"",
+            "[profiles.a]\nplaceholders = [\"carga horaria\"]",
+            "[profiles.a]\nplaceholders = [\"\"]",
+            "[profiles.a]\nrequired_placeholders = [\"curso\"]",
+            "[profiles.a]\n[profiles.a.schema]\ntabela = \"x\"",
+        ] {
+            let profile = Config::parse(src).and_then(|config| config.profile(Some("a")));
+            assert!(profile.is_err(), "{src}");
+        }
+    }
+
+    #[test]
+    fn profile_placeholders() {
+        let src = "[profiles.curso]\n\
+                   placeholders = [\"curso\", \"turma\"]\n\
+                   required_placeholders = [\"nome\", \"curso\"]\n";
+        let config = Config::parse(src).expect("valid config");
+        let placeholders = config.profile(Some("curso")).unwrap().placeholders;
+        assert_eq!(
+            Ok(vec!["nome", "curso", "turma"]),
+            placeholders.validate("#nome# do #curso# na #turma#")
+        );
+        assert!(placeholders.validate("#nome# na #turma#").is_err());
+        assert!(placeholders
+            .validate("#curso# de #nome# em #local#")
+            .is_err());
+    }
+}
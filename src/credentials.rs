@@ -0,0 +1,163 @@
+use age::secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Default credentials file, relative to the current directory.
+pub const DEFAULT_PATH: &str = "pet-scroll.age";
+
+#[derive(Debug)]
+pub enum CredentialsError {
+    Encrypt(age::EncryptError),
+    /// A wrong passphrase or a damaged file.
+    Decrypt(age::DecryptError),
+    Format(csv::Error),
+}
+
+impl std::fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encrypt(err) => write!(f, "failed to encrypt the credentials: {err}"),
+            Self::Decrypt(err) => write!(f, "failed to decrypt the credentials: {err}"),
+            Self::Format(err) => write!(f, "invalid credentials: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CredentialsError {}
+
+impl From<csv::Error> for CredentialsError {
+    fn from(err: csv::Error) -> Self {
+        Self::Format(err)
+    }
+}
+
+/// An entry of the credentials file.
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    #[serde(rename = "PERFIL")]
+    profile: String,
+    #[serde(rename = "NOME")]
+    name: String,
+    #[serde(rename = "SEGREDO")]
+    secret: String,
+}
+
+/// Secrets of each profile, like `SFTP_PWD`, stored as CSV encrypted with a
+/// passphrase in the age format.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// Secrets by profile and name.
+    secrets: BTreeMap<(String, String), String>,
+}
+
+impl Credentials {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decrypt the contents of a credentials file.
+    pub fn decrypt(file: &[u8], passphrase: &str) -> Result<Self, CredentialsError> {
+        let identity = age::scrypt::Identity::new(SecretString::from(passphrase.to_owned()));
+        let csv = age::decrypt(&identity, file).map_err(CredentialsError::Decrypt)?;
+        let mut secrets = BTreeMap::new();
+        for entry in csv::Reader::from_reader(csv.as_slice()).deserialize() {
+            let Entry {
+                profile,
+                name,
+                secret,
+            } = entry?;
+            secrets.insert((profile, name), secret);
+        }
+        Ok(Self { secrets })
+    }
+
+    /// Encrypt the credentials into the contents of a credentials file, with
+    /// the scrypt `work_factor` or the one calibrated by age to take about a
+    /// second.
+    pub fn encrypt(
+        &self,
+        passphrase: &str,
+        work_factor: Option<u8>,
+    ) -> Result<Vec<u8>, CredentialsError> {
+        let mut wtr = csv::Writer::from_writer(Vec::new());
+        for ((profile, name), secret) in &self.secrets {
+            wtr.serialize(Entry {
+                profile: profile.clone(),
+                name: name.clone(),
+                secret: secret.clone(),
+            })?;
+        }
+        let csv = wtr
+            .into_inner()
+            .map_err(|err| CredentialsError::Format(err.into_error().into()))?;
+
+        let mut recipient = age::scrypt::Recipient::new(SecretString::from(passphrase.to_owned()));
+        if let Some(work_factor) = work_factor {
+            recipient.set_work_factor(work_factor);
+        }
+        age::encrypt(&recipient, &csv).map_err(CredentialsError::Encrypt)
+    }
+
+    pub fn get(&self, profile: &str, name: &str) -> Option<&str> {
+        self.secrets
+            .get(&(profile.to_owned(), name.to_owned()))
+            .map(String::as_str)
+    }
+
+    /// Add or replace a secret, returning the previous one.
+    pub fn set(&mut self, profile: &str, name: &str, secret: String) -> Option<String> {
+        self.secrets
+            .insert((profile.to_owned(), name.to_owned()), secret)
+    }
+
+    pub fn remove(&mut self, profile: &str, name: &str) -> Option<String> {
+        self.secrets.remove(&(profile.to_owned(), name.to_owned()))
+    }
+
+    /// Profiles and names of the stored secrets.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.secrets
+            .keys()
+            .map(|(profile, name)| (profile.as_str(), name.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_credentials() {
+        let mut credentials = Credentials::new();
+        assert_eq!(
+            None,
+            credentials.set("prod", "SFTP_PWD", "senha123".to_owned())
+        );
+        credentials.set("prod", "DB_PWD", "outra, senha".to_owned());
+        credentials.set("staging", "SFTP_PWD", "teste".to_owned());
+        assert_eq!(
+            Some("senha123".to_owned()),
+            credentials.set("prod", "SFTP_PWD", "nova".to_owned())
+        );
+
+        let file = credentials
+            .encrypt("passphrase", Some(10))
+            .expect("encrypted");
+        assert!(!file.windows(4).any(|part| part == b"nova"));
+        let read = Credentials::decrypt(&file, "passphrase").expect("decrypted");
+        assert_eq!(credentials, read);
+        assert_eq!(Some("nova"), read.get("prod", "SFTP_PWD"));
+        assert_eq!(Some("outra, senha"), read.get("prod", "DB_PWD"));
+        assert_eq!(None, read.get("default", "SFTP_PWD"));
+        assert!(matches!(
+            Credentials::decrypt(&file, "wrong"),
+            Err(CredentialsError::Decrypt(_))
+        ));
+
+        credentials.remove("prod", "DB_PWD");
+        assert_eq!(
+            vec![("prod", "SFTP_PWD"), ("staging", "SFTP_PWD")],
+            credentials.entries().collect::<Vec<_>>()
+        );
+    }
+}
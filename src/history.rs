@@ -0,0 +1,679 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::cert::{
+    correction::Correction,
+    csv_data::{EventDesc, Workload},
+    revocation::{Revocation, Revoked},
+    Certificate,
+};
+
+/// Default history file, read from the current directory.
+pub const DEFAULT_PATH: &str = "pet-scroll.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS import (
+    id INTEGER PRIMARY KEY,
+    created TEXT NOT NULL,
+    operator TEXT NOT NULL,
+    profile TEXT NOT NULL,
+    database TEXT NOT NULL,
+    event_id INTEGER,
+    event TEXT NOT NULL,
+    date TEXT NOT NULL,
+    text TEXT NOT NULL,
+    img TEXT NOT NULL,
+    input_hash TEXT NOT NULL,
+    output TEXT NOT NULL,
+    upload TEXT
+);
+CREATE TABLE IF NOT EXISTS participation (
+    import INTEGER NOT NULL REFERENCES import(id),
+    name TEXT NOT NULL,
+    cpf TEXT NOT NULL,
+    role TEXT NOT NULL,
+    text TEXT NOT NULL,
+    workload INTEGER NOT NULL,
+    code TEXT
+);
+CREATE INDEX IF NOT EXISTS participation_cpf ON participation(cpf);
+CREATE TABLE IF NOT EXISTS amendment (
+    id INTEGER PRIMARY KEY,
+    created TEXT NOT NULL,
+    operator TEXT NOT NULL,
+    profile TEXT NOT NULL,
+    database TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    cpf TEXT,
+    event_id INTEGER,
+    code TEXT,
+    detail TEXT NOT NULL,
+    input_hash TEXT NOT NULL,
+    output TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS amendment_cpf ON amendment(cpf);
+";
+
+const IMPORT_COLUMNS: &str = "id, created, operator, profile, database, event_id, event, date, \
+    text, img, input_hash, output, upload";
+
+const AMENDMENT_COLUMNS: &str = "id, created, operator, profile, database, kind, cpf, event_id, \
+    code, detail, input_hash, output";
+
+/// Where an import script came from and where it went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    pub operator: String,
+    /// Profile or organization used.
+    pub profile: String,
+    pub database: String,
+    /// Hash of the event and attendees files.
+    pub input_hash: String,
+    /// Path of the SQL file.
+    pub output: String,
+}
+
+/// An import script generated for a certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub id: i64,
+    /// UTC time of the generation in RFC 3339.
+    pub created: String,
+    pub source: Source,
+    /// Id of an existing event.
+    pub event_id: Option<u32>,
+    pub event: String,
+    pub date: String,
+    /// Default text of the event, or its id.
+    pub text: String,
+    pub img: String,
+    /// `ok` or the error of the image upload, if any.
+    pub upload: Option<String>,
+}
+
+impl Import {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            created: row.get(1)?,
+            source: Source {
+                operator: row.get(2)?,
+                profile: row.get(3)?,
+                database: row.get(4)?,
+                input_hash: row.get(10)?,
+                output: row.get(11)?,
+            },
+            event_id: row.get(5)?,
+            event: row.get(6)?,
+            date: row.get(7)?,
+            text: row.get(8)?,
+            img: row.get(9)?,
+            upload: row.get(12)?,
+        })
+    }
+}
+
+impl std::fmt::Display for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Import:   #{}", self.id)?;
+        writeln!(f, "Created:  {} by {}", self.created, self.source.operator)?;
+        writeln!(
+            f,
+            "Profile:  {} ({})",
+            self.source.profile, self.source.database
+        )?;
+        match self.event_id {
+            Some(id) => writeln!(f, "Event:    {} (#{id})", self.event)?,
+            None => writeln!(f, "Event:    {}", self.event)?,
+        }
+        writeln!(f, "Date:     {}", self.date)?;
+        writeln!(f, "Text:     {}", self.text)?;
+        if !self.img.is_empty() {
+            writeln!(f, "Image:    {}", self.img)?;
+        }
+        writeln!(f, "Input:    {}", self.source.input_hash)?;
+        write!(f, "Output:   {}", self.source.output)?;
+        if let Some(upload) = &self.upload {
+            write!(f, "\nUpload:   {upload}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An attendee of an import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Participation {
+    pub name: String,
+    /// CPF or other identification of the attendee.
+    pub cpf: String,
+    pub role: String,
+    /// Text of the attendee, or its id.
+    pub text: String,
+    pub workload: Workload,
+    pub code: Option<String>,
+}
+
+impl Participation {
+    /// Read a participation from the columns after `offset`.
+    fn from_row(row: &Row, offset: usize) -> rusqlite::Result<Self> {
+        Ok(Self {
+            name: row.get(offset)?,
+            cpf: row.get(offset + 1)?,
+            role: row.get(offset + 2)?,
+            text: row.get(offset + 3)?,
+            workload: Workload::from_minutes(row.get(offset + 4)?),
+            code: row.get(offset + 5)?,
+        })
+    }
+}
+
+/// What an amendment did to the issued certificates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmendmentKind {
+    Revocation,
+    Correction,
+}
+
+impl AmendmentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Revocation => "revocation",
+            Self::Correction => "correction",
+        }
+    }
+}
+
+/// A revocation or correction script of issued certificates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Amendment {
+    pub id: i64,
+    /// UTC time of the generation in RFC 3339.
+    pub created: String,
+    pub source: Source,
+    pub kind: AmendmentKind,
+    /// CPF or other identification of the attendee, unknown for the
+    /// revocation of a code missing in the history.
+    pub cpf: Option<String>,
+    /// Id of the event in the database, if known.
+    pub event_id: Option<u32>,
+    pub code: Option<String>,
+    /// Reason of a revocation, or the changes of a correction.
+    pub detail: String,
+}
+
+impl Amendment {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let kind = match row.get_ref(5)?.as_str()? {
+            "revocation" => AmendmentKind::Revocation,
+            _ => AmendmentKind::Correction,
+        };
+        Ok(Self {
+            id: row.get(0)?,
+            created: row.get(1)?,
+            source: Source {
+                operator: row.get(2)?,
+                profile: row.get(3)?,
+                database: row.get(4)?,
+                input_hash: row.get(10)?,
+                output: row.get(11)?,
+            },
+            kind,
+            cpf: row.get(6)?,
+            event_id: row.get(7)?,
+            code: row.get(8)?,
+            detail: row.get(9)?,
+        })
+    }
+}
+
+impl std::fmt::Display for Amendment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "#{} {} {} by {}",
+            self.id,
+            self.created,
+            self.kind.as_str(),
+            self.source.operator
+        )?;
+        if let Some(cpf) = &self.cpf {
+            write!(f, " of {cpf}")?;
+        }
+        if let Some(id) = self.event_id {
+            write!(f, " in event #{id}")?;
+        }
+        if let Some(code) = &self.code {
+            write!(f, ", code {code}")?;
+        }
+        write!(f, ": {} ({})", self.detail, self.source.output)
+    }
+}
+
+/// Current UTC time in RFC 3339, without fractions of a second.
+fn now() -> String {
+    OffsetDateTime::now_utc()
+        .replace_nanosecond(0)
+        .expect("zero is a valid nanosecond")
+        .format(&Rfc3339)
+        .expect("the current time has a RFC 3339 format")
+}
+
+fn desc_text(desc: &EventDesc) -> String {
+    match desc {
+        EventDesc::Id(id) => id.to_string(),
+        EventDesc::Text(text) => text.clone(),
+    }
+}
+
+/// Local SQLite history of the generated import scripts.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Open the history at `path`, creating it if needed.
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> rusqlite::Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// A history kept only in memory.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Record the import script of a certificate, returning its id.
+    pub fn record(&mut self, cert: &Certificate, source: &Source) -> rusqlite::Result<i64> {
+        let created = now();
+        let event = cert.event();
+        let data = event.data();
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO import (created, operator, profile, database, event_id, event, date, \
+            text, img, input_hash, output) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                created,
+                source.operator,
+                source.profile,
+                source.database,
+                data.id,
+                data.name,
+                data.date.to_string(),
+                desc_text(&data.desc),
+                cert.img(),
+                source.input_hash,
+                source.output,
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        for att in event.attendees() {
+            tx.execute(
+                "INSERT INTO participation (import, name, cpf, role, text, workload, code) \
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    id,
+                    att.name,
+                    att.id.as_str(),
+                    att.role.as_str(),
+                    desc_text(data.desc(att.role)),
+                    att.workload.minutes(),
+                    event.code(att).map(|code| code.to_string()),
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn add_amendment(conn: &Connection, amendment: &Amendment) -> rusqlite::Result<i64> {
+        conn.execute(
+            "INSERT INTO amendment (created, operator, profile, database, kind, cpf, event_id, \
+            code, detail, input_hash, output) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                amendment.created,
+                amendment.source.operator,
+                amendment.source.profile,
+                amendment.source.database,
+                amendment.kind.as_str(),
+                amendment.cpf,
+                amendment.event_id,
+                amendment.code,
+                amendment.detail,
+                amendment.source.input_hash,
+                amendment.source.output,
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record a revocation script with its reason, returning its id.
+    ///
+    /// The attendee and event of a revoked code are the ones of its import.
+    pub fn record_revocation(
+        &mut self,
+        revocation: &Revocation,
+        source: &Source,
+    ) -> rusqlite::Result<i64> {
+        let (cpf, event_id, code) = match &revocation.revoked {
+            Revoked::Code(code) => {
+                let issued = self
+                    .conn
+                    .query_row(
+                        "SELECT p.cpf, i.event_id FROM participation p \
+                        JOIN import i ON i.id = p.import WHERE p.code=?1 \
+                        ORDER BY i.id DESC LIMIT 1",
+                        [code.as_str()],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()?;
+                let (cpf, event_id) = issued.unzip();
+                (cpf, event_id.flatten(), Some(code.to_string()))
+            }
+            Revoked::Attendee { id, event } => (Some(id.as_str().to_owned()), Some(*event), None),
+        };
+        Self::add_amendment(
+            &self.conn,
+            &Amendment {
+                id: 0,
+                created: now(),
+                source: source.clone(),
+                kind: AmendmentKind::Revocation,
+                cpf,
+                event_id,
+                code,
+                detail: revocation.reason.clone(),
+            },
+        )
+    }
+
+    /// Record a corrections script, with an amendment per corrected
+    /// certificate.
+    pub fn record_corrections(
+        &mut self,
+        corrections: &[Correction],
+        source: &Source,
+    ) -> rusqlite::Result<()> {
+        let created = now();
+        let tx = self.conn.transaction()?;
+        for correction in corrections {
+            let changes = correction
+                .changes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            Self::add_amendment(
+                &tx,
+                &Amendment {
+                    id: 0,
+                    created: created.clone(),
+                    source: source.clone(),
+                    kind: AmendmentKind::Correction,
+                    cpf: Some(correction.id.as_str().to_owned()),
+                    event_id: Some(correction.event),
+                    code: None,
+                    detail: changes.join(", "),
+                },
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Record the result of the image upload of an import.
+    pub fn set_upload(&self, id: i64, result: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "UPDATE import SET upload=?1 WHERE id=?2",
+            params![result, id],
+        )?;
+        Ok(())
+    }
+
+    /// Every import, oldest first.
+    pub fn imports(&self) -> rusqlite::Result<Vec<Import>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {IMPORT_COLUMNS} FROM import ORDER BY id"))?;
+        let imports = stmt.query_map([], Import::from_row)?;
+        imports.collect()
+    }
+
+    /// An import with its participations.
+    pub fn import(&self, id: i64) -> rusqlite::Result<Option<(Import, Vec<Participation>)>> {
+        let import = self
+            .conn
+            .query_row(
+                &format!("SELECT {IMPORT_COLUMNS} FROM import WHERE id=?1"),
+                [id],
+                Import::from_row,
+            )
+            .optional()?;
+        let Some(import) = import else {
+            return Ok(None);
+        };
+        let mut stmt = self.conn.prepare(
+            "SELECT name, cpf, role, text, workload, code FROM participation \
+            WHERE import=?1 ORDER BY rowid",
+        )?;
+        let participations = stmt
+            .query_map([id], |row| Participation::from_row(row, 0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(Some((import, participations)))
+    }
+
+    /// Revocations and corrections of a CPF or other identification.
+    pub fn amendments(&self, cpf: &str) -> rusqlite::Result<Vec<Amendment>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {AMENDMENT_COLUMNS} FROM amendment WHERE cpf=?1 ORDER BY id"
+        ))?;
+        let amendments = stmt.query_map([cpf], Amendment::from_row)?;
+        amendments.collect()
+    }
+
+    /// Revocations and corrections of the participations of an import,
+    /// found by their codes or by the event id of an existing event.
+    pub fn import_amendments(&self, id: i64) -> rusqlite::Result<Vec<Amendment>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {AMENDMENT_COLUMNS} FROM amendment \
+            WHERE code IN (SELECT code FROM participation WHERE import=?1) \
+            OR (event_id=(SELECT event_id FROM import WHERE id=?1) \
+            AND database=(SELECT database FROM import WHERE id=?1) \
+            AND cpf IN (SELECT cpf FROM participation WHERE import=?1)) \
+            ORDER BY id"
+        ))?;
+        let amendments = stmt.query_map([id], Amendment::from_row)?;
+        amendments.collect()
+    }
+
+    /// Participations of a CPF or other identification in every import.
+    pub fn history(&self, cpf: &str) -> rusqlite::Result<Vec<(Import, Participation)>> {
+        let columns = IMPORT_COLUMNS
+            .split(", ")
+            .map(|column| format!("i.{column}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {columns}, p.name, p.cpf, p.role, p.text, p.workload, p.code \
+            FROM participation p JOIN import i ON i.id = p.import \
+            WHERE p.cpf=?1 ORDER BY i.id"
+        ))?;
+        let rows = stmt.query_map([cpf], |row| {
+            Ok((Import::from_row(row)?, Participation::from_row(row, 13)?))
+        })?;
+        rows.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{
+        code::Signer,
+        csv_data::{Attendee, Cpf, EventData, EventDate, Role},
+    };
+    use std::collections::BTreeMap;
+    use time::macros::date;
+
+    fn cert(name: &str, signer: Option<Signer>) -> Certificate {
+        let data = EventData {
+            id: None,
+            name: name.to_owned(),
+            desc: EventDesc::Text("#nome# participou".to_owned()),
+            date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::from([(Role::Speaker, EventDesc::Id(7))]),
+            max_workload: None,
+        };
+        let atts = vec![
+            Attendee {
+                name: "A".to_owned(),
+                id: Cpf::new("754.751.875-33".to_owned())
+                    .expect("valid cpf")
+                    .into(),
+                workload: Workload::from_minutes(90),
+                role: Role::Speaker,
+            },
+            Attendee {
+                name: "B".to_owned(),
+                id: Cpf::new("647.748.630-09".to_owned())
+                    .expect("valid cpf")
+                    .into(),
+                workload: Workload::from_hours(2),
+                role: Role::Participant,
+            },
+        ];
+        let event = data.into_event(atts);
+        match signer {
+            Some(signer) => event.sign(signer),
+            None => event,
+        }
+        .into_cert("img/cert.png".to_owned())
+    }
+
+    #[test]
+    fn record_imports() {
+        let mut history = History::open_in_memory().expect("history");
+        let source = Source {
+            operator: "pet".to_owned(),
+            profile: "prod".to_owned(),
+            database: "petcomp".to_owned(),
+            input_hash: "abc".to_owned(),
+            output: "event.sql".to_owned(),
+        };
+        let first = history
+            .record(&cert("Event", None), &source)
+            .expect("recorded");
+        let second = history
+            .record(&cert("Other", Some(Signer::new(b"secret"))), &source)
+            .expect("recorded");
+        history.set_upload(second, "ok").expect("upload recorded");
+
+        let imports = history.imports().expect("imports");
+        assert_eq!(
+            vec![first, second],
+            imports.iter().map(|import| import.id).collect::<Vec<_>>()
+        );
+        assert_eq!(source, imports[0].source);
+        assert_eq!("dia 04/05/2023", imports[0].date);
+        assert_eq!("#nome# participou", imports[0].text);
+        assert_eq!("img/cert.png", imports[0].img);
+        assert_eq!(None, imports[0].upload);
+        assert_eq!(Some("ok"), imports[1].upload.as_deref());
+
+        let (import, participations) = history
+            .import(first)
+            .expect("import")
+            .expect("existing import");
+        assert_eq!(imports[0], import);
+        assert_eq!(
+            vec![
+                Participation {
+                    name: "A".to_owned(),
+                    cpf: "754.751.875-33".to_owned(),
+                    role: "palestrante".to_owned(),
+                    text: "7".to_owned(),
+                    workload: Workload::from_minutes(90),
+                    code: None,
+                },
+                Participation {
+                    name: "B".to_owned(),
+                    cpf: "647.748.630-09".to_owned(),
+                    role: "participante".to_owned(),
+                    text: "#nome# participou".to_owned(),
+                    workload: Workload::from_hours(2),
+                    code: None,
+                },
+            ],
+            participations
+        );
+        assert_eq!(None, history.import(42).expect("no import"));
+
+        let found = history.history("647.748.630-09").expect("history");
+        assert_eq!(2, found.len());
+        assert_eq!("Event", found[0].0.event);
+        assert_eq!("Other", found[1].0.event);
+        assert!(found[1].1.code.is_some());
+        assert!(history
+            .history("000.000.000-00")
+            .expect("history")
+            .is_empty());
+    }
+
+    #[test]
+    fn record_amendments() {
+        let mut history = History::open_in_memory().expect("history");
+        let source = Source {
+            operator: "pet".to_owned(),
+            profile: "prod".to_owned(),
+            database: "petcomp".to_owned(),
+            input_hash: "abc".to_owned(),
+            output: "fix.sql".to_owned(),
+        };
+        let cert = cert("Event", Some(Signer::new(b"secret")));
+        let import = history.record(&cert, &source).expect("recorded");
+        let code = cert.event().code(&cert.event().attendees()[0]).unwrap();
+
+        let mut revocation = Revocation {
+            revoked: Revoked::Code(code.clone()),
+            reason: "Presença falsa".to_owned(),
+            flag: false,
+        };
+        history
+            .record_revocation(&revocation, &source)
+            .expect("recorded");
+        revocation.revoked = Revoked::Attendee {
+            id: Cpf::new("647.748.630-09".to_owned()).unwrap().into(),
+            event: 12,
+        };
+        history
+            .record_revocation(&revocation, &source)
+            .expect("recorded");
+        let sheet = "CPF,EVENTO,NOME_ATUAL,NOME_NOVO,CH_ATUAL,CH_NOVA\n\
+                     75475187533,12,A,Ana,1h30,1\n";
+        let corrections = crate::cert::correction::read_corrections(sheet.as_bytes()).unwrap();
+        history
+            .record_corrections(&corrections, &source)
+            .expect("recorded");
+
+        let found = history.amendments("754.751.875-33").expect("amendments");
+        assert_eq!(2, found.len());
+        assert_eq!(AmendmentKind::Revocation, found[0].kind);
+        assert_eq!(Some(code.to_string()), found[0].code);
+        assert_eq!(None, found[0].event_id);
+        assert_eq!("Presença falsa", found[0].detail);
+        assert_eq!(AmendmentKind::Correction, found[1].kind);
+        assert_eq!(Some(12), found[1].event_id);
+        assert_eq!("name A -> Ana, workload 1h30 -> 1h", found[1].detail);
+        assert!(found[1].to_string().ends_with(
+            "correction by pet of 754.751.875-33 in event #12: \
+                name A -> Ana, workload 1h30 -> 1h (fix.sql)"
+        ));
+
+        let revoked = history.import_amendments(import).expect("amendments");
+        assert_eq!(vec![found[0].clone()], revoked);
+        assert_eq!(1, history.amendments("647.748.630-09").unwrap().len());
+    }
+}
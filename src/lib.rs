@@ -1,3 +1,9 @@
 pub mod cert;
+pub mod config;
+pub mod credentials;
+pub mod history;
+pub mod org;
+pub mod pdf;
 pub mod sftp;
 pub mod sql;
+pub mod template;
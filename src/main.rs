@@ -1,12 +1,75 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use pet_scroll::{
-    cert::csv_data::{Attendee, EventData},
+    cert::{
+        code::Signer,
+        csv_data::{Attendee, EventData, IdKind, Identification, Rounding},
+        duplicate::{self, Dedup, NamePolicy, WorkloadPolicy},
+        ledger::Ledger,
+        name::{self, NameCase},
+        Event, Severity,
+    },
+    config::{Config, Profile, SftpAuth},
+    credentials::Credentials,
+    history::{History, Source},
+    org::Org,
+    sftp::Auth,
     sql::ToSQL,
+    template::Template,
 };
-use std::{io::Write, path::PathBuf};
+use sha2::{Digest, Sha256};
+use std::{
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+mod cmd;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
+struct Cli {
+    /// Configuration file, by default pet-scroll.toml in the current
+    /// directory or its parents if it exists.
+    #[arg(long, global = true, value_parser = existing_file)]
+    config: Option<PathBuf>,
+    /// Profile of the configuration file to use.
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Organization of the configuration file to use, required to generate
+    /// SQL when the file has organizations.
+    #[arg(long, global = true, conflicts_with = "profile")]
+    org: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    args: Option<Args>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Render the event certificates as PDF files.
+    Render(cmd::render::Args),
+    /// Check whether a certificate was issued.
+    Verify(cmd::verify::Args),
+    /// Suggest corrections for the invalid CPFs of an attendees file.
+    CheckCpf(cmd::check_cpf::Args),
+    /// Compute the attendees workloads from session presence sheets.
+    Attendance(cmd::attendance::Args),
+    /// Compute the attendees workloads from online meeting reports.
+    Meeting(cmd::meeting::Args),
+    /// Compare the registrations with the attendance of an event.
+    Reconcile(cmd::reconcile::Args),
+    /// Generate the SQL to correct issued certificates.
+    Correct(cmd::correct::Args),
+    /// Generate the SQL to revoke an issued certificate.
+    Revoke(cmd::revoke::Args),
+    /// Manage the encrypted secrets of the profile.
+    Credentials(cmd::credentials::Args),
+    /// Look up the generated import scripts.
+    History(cmd::history::Args),
+}
+
+/// Write the SQL import script of an event.
+#[derive(clap::Args, Debug)]
 struct Args {
     /// Event Data CSV file.
     #[arg(short, long, value_parser = existing_file)]
@@ -14,7 +77,21 @@ struct Args {
     /// Attendees Info CSV file.
     #[arg(short, long = "atts", value_parser = existing_file)]
     attendees: PathBuf,
-    /// An already uploaded event certificate image.
+    /// Keep the attendee names casing exactly as in the attendees file.
+    #[arg(long)]
+    keep_name_case: bool,
+    /// How the workloads of a repeated attendee are merged: sum, max or reject.
+    #[arg(long, default_value = "max")]
+    duplicate_hours: WorkloadPolicy,
+    /// Which name of a repeated attendee is kept: first, longest or ask.
+    #[arg(long, default_value = "first")]
+    duplicate_names: NamePolicy,
+    /// How workloads are rounded: ceil, floor, nearest or half-hour,
+    /// by default the one of the profile or ceil.
+    #[arg(long)]
+    rounding: Option<Rounding>,
+    /// An already uploaded event certificate image, not needed to add
+    /// attendees to an event with an ID.
     #[arg(short, long, group = "image")]
     cert_img: Option<PathBuf>,
     /// Uploads the given event certificate image to the SFTP server.
@@ -25,8 +102,127 @@ struct Args {
     #[arg(short, long, value_parser = existing_file, group = "image")]
     upload_img: Option<PathBuf>,
     /// SQL output file.
-    #[arg(short, long, requires = "image")]
+    #[arg(short, long)]
     output: PathBuf,
+    /// Issue a verification code for each participation.
+    ///
+    /// The codes are generated from the secret key in the CERT_SECRET
+    /// environment variable and inserted into the `participacao.codigo`
+    /// column.
+    #[arg(long)]
+    codes: bool,
+    /// Record the issued certificates into this ledger CSV file.
+    #[arg(short, long, requires = "codes")]
+    ledger: Option<PathBuf>,
+    /// Refuse to generate the SQL when the event has warnings, also enabled
+    /// by the `strict` setting of the profile.
+    #[arg(long)]
+    strict: bool,
+    /// Generate the SQL through this template instead of the built-in
+    /// queries.
+    #[arg(long, value_parser = existing_file)]
+    sql_template: Option<PathBuf>,
+}
+
+/// Secrets of the profile in its credentials file, falling back to the
+/// environment variables.
+struct Secrets<'a> {
+    profile: &'a Profile,
+    credentials: Option<Credentials>,
+}
+
+impl<'a> Secrets<'a> {
+    fn load(profile: &'a Profile) -> std::io::Result<Self> {
+        let _ = dotenvy::dotenv();
+        let credentials = cmd::credentials::load(&profile.credentials)?;
+        Ok(Self {
+            profile,
+            credentials: credentials.map(|(credentials, _)| credentials),
+        })
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        self.credentials
+            .as_ref()
+            .and_then(|credentials| credentials.get(&self.profile.name, name))
+            .map(str::to_owned)
+            .or_else(|| std::env::var(name).ok())
+    }
+
+    /// Signer using the CERT_SECRET key, if defined.
+    fn signer(&self) -> Option<Signer> {
+        self.get("CERT_SECRET")
+            .map(|key| Signer::new(key.as_bytes()))
+    }
+}
+
+/// Signer using the CERT_SECRET key of the credentials or environment.
+fn signer(profile: &Profile) -> std::io::Result<Signer> {
+    let signer = Secrets::load(profile)?
+        .signer()
+        .expect("CERT_SECRET not found in the credentials or environment");
+    Ok(signer)
+}
+
+fn name_case(keep: bool) -> NameCase {
+    if keep {
+        NameCase::Preserve
+    } else {
+        NameCase::Normalize
+    }
+}
+
+/// Round the event workloads reporting the ones that changed, failing on
+/// workloads shorter than one rounding step.
+fn round(event: &mut Event, rounding: Rounding) {
+    let (rounded, short): (Vec<_>, Vec<_>) = event
+        .round(rounding)
+        .into_iter()
+        .partition(|att| att.to.is_some());
+    if !rounded.is_empty() {
+        println!("Rounded {} workloads:", rounded.len());
+    }
+    for att in rounded {
+        let to = att.to.expect("rounded workload");
+        println!("    {}: {} -> {}", att.name, att.from.hm(), to.hm());
+    }
+    for att in &short {
+        println!(
+            "error: {} has {}, less than one step of the {rounding:?} rounding",
+            att.name,
+            att.from.hm()
+        );
+    }
+    if !short.is_empty() {
+        panic!(
+            "{} workload(s) would round to zero, fix them or choose another rounding",
+            short.len()
+        );
+    }
+}
+
+fn id_kind(s: &str) -> Result<IdKind, String> {
+    IdKind::try_from(s.to_owned()).map_err(|err| err.to_string())
+}
+
+/// Parse the `--id` of a command as an identification of the given kind.
+fn identification(kind: IdKind, value: String) -> Identification {
+    Identification::new(kind, value).unwrap_or_else(|err| panic!("Invalid identification: {err}"))
+}
+
+/// Report the event issues, failing on errors, or on warnings if `strict`.
+fn validate(event: &Event, img: Option<&str>, strict: bool) {
+    let issues = event.validate(img);
+    for issue in &issues {
+        println!("{issue}");
+    }
+    let failed = issues
+        .iter()
+        .filter(|issue| strict || issue.severity == Severity::Error)
+        .count();
+    if failed > 0 {
+        panic!("The event has {failed} issue(s), fix them before generating the SQL");
+    }
 }
 
 fn existing_file(s: &str) -> Result<PathBuf, String> {
@@ -38,13 +234,25 @@ fn existing_file(s: &str) -> Result<PathBuf, String> {
 }
 
 /// Read event attendees from the given `src`.
-fn attendees<S>(src: S) -> Vec<Attendee>
+fn attendees<S>(src: S, case: NameCase) -> Vec<Attendee>
 where
     S: std::io::Read,
 {
     let mut rdr = csv::Reader::from_reader(src);
-    rdr.deserialize()
-        .map(|result| result.expect("Error while parsing STDIN"))
+    let headers = rdr.headers().expect("Error while parsing STDIN").clone();
+    let name_col = headers.iter().position(|col| col == "NOME");
+    rdr.records()
+        .map(|result| {
+            let record = result.expect("Error while parsing STDIN");
+            let mut att: Attendee = record
+                .deserialize(Some(&headers))
+                .expect("Error while parsing STDIN");
+            // the deserialized name is always normalized
+            if let (NameCase::Preserve, Some(raw)) = (case, name_col.and_then(|i| record.get(i))) {
+                att.name = name::normalize(raw, case);
+            }
+            att
+        })
         .collect::<Vec<Attendee>>()
 }
 
@@ -60,59 +268,303 @@ where
         .expect("Error while parsing STDIN")
 }
 
-fn main() -> std::io::Result<()> {
-    let args = Args::parse();
-    let img_name = match (&args.cert_img, &args.upload_img) {
-        (Some(img), None) | (None, Some(img)) => img
-            .file_name()
-            .expect("Error while getting image file name")
-            .to_str()
-            .expect("Thi image name is not a valid UTF-8")
-            .split_whitespace()
-            .map(|word| word.to_lowercase())
-            .collect::<Vec<_>>()
-            .join("_"),
-        _ => unreachable!("Both args should not be provided at the same time"),
-    };
-
+/// Read the event file at `path` reporting the progress, with the `@name`
+/// texts of the organization catalog replaced.
+fn read_event(path: &Path, profile: &Profile, org: Option<&Org>) -> std::io::Result<EventData> {
     print!("Reading event file...");
     std::io::stdout().flush()?;
-    let evt_file = std::fs::File::open(args.event)?;
+    let evt_file = std::fs::File::open(path)?;
     let buffer = std::io::BufReader::new(evt_file);
-    let evt = event_data(buffer);
+    let mut evt = event_data(buffer);
+    if let Some(org) = org {
+        org.resolve_texts(&mut evt)
+            .unwrap_or_else(|err| panic!("Invalid event text: {err}"));
+    }
+    evt.check_texts(&profile.placeholders)
+        .unwrap_or_else(|err| panic!("Invalid event text: {err}"));
     println!(" Done!");
+    Ok(evt)
+}
+
+/// Ask which of the `names` of a repeated attendee to keep.
+fn ask_name(id: &Identification, names: &[String]) -> String {
+    println!("{} is listed with different names:", id.as_str());
+    for (i, name) in names.iter().enumerate() {
+        println!("    {}) {name}", i + 1);
+    }
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        print!("Name to keep [1-{}]: ", names.len());
+        std::io::stdout()
+            .flush()
+            .expect("failed to write into STDOUT");
+        let answer = match lines.next() {
+            Some(answer) => answer.expect("Error while reading STDIN"),
+            None => return names[0].clone(),
+        };
+        match answer.trim().parse::<usize>() {
+            Ok(i) if (1..=names.len()).contains(&i) => return names[i - 1].clone(),
+            _ => continue,
+        }
+    }
+}
+
+/// SHA-256 of the SHA-256 of each input file.
+fn input_hash(paths: &[&Path]) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    for path in paths {
+        hasher.update(Sha256::digest(std::fs::read(path)?));
+    }
+    Ok(data_encoding::HEXLOWER.encode(&hasher.finalize()))
+}
+
+/// Open the history of the profile, with the source of a script generated
+/// from inputs with `input_hash` into `output`.
+fn open_history(profile: &Profile, input_hash: String, output: &Path) -> (History, Source) {
+    let history = History::open(&profile.history).unwrap_or_else(|err| {
+        panic!(
+            "Error while opening the history {}: {err}",
+            profile.history.display()
+        )
+    });
+    let source = Source {
+        operator: operator(),
+        profile: profile.name.clone(),
+        database: profile.schema.database().to_owned(),
+        input_hash,
+        output: output.display().to_string(),
+    };
+    (history, source)
+}
 
+/// Name of the user running pet-scroll.
+fn operator() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_owned())
+}
+
+/// Read the attendees file at `path` reporting the progress.
+///
+/// Repeated attendees are merged with the `dedup` policies and attendees
+/// with similar names are reported.
+fn read_attendees(path: &Path, case: NameCase, dedup: Dedup) -> std::io::Result<Vec<Attendee>> {
     print!("Reading attendees file...");
     std::io::stdout().flush()?;
-    let atts_file = std::fs::File::open(args.attendees)?;
+    let atts_file = std::fs::File::open(path)?;
     let buffer = std::io::BufReader::new(atts_file);
-    let atts = attendees(buffer);
+    let atts = attendees(buffer, case);
     println!(" Done!");
 
-    let cert = evt.into_event(atts).into_cert(format!("img/{img_name}"));
-    let queries = cert.to_sql().into_req("petcomp").to_string();
+    let (atts, merges) = dedup.merge(atts, ask_name).unwrap_or_else(|err| {
+        panic!("Repeated attendee, use --duplicate-hours to merge them: {err}")
+    });
+    if !merges.is_empty() {
+        println!("Merged {} repeated attendees:", merges.len());
+    }
+    for merge in &merges {
+        println!("    {merge}");
+    }
+    for (a, b) in duplicate::similar_names(&atts) {
+        println!(
+            "Warning: {} ({}) and {} ({}) may be the same person",
+            a.name,
+            a.id.as_str(),
+            b.name,
+            b.id.as_str()
+        );
+    }
+    Ok(atts)
+}
+
+/// Write an attendees file at `path` reporting the progress.
+fn write_attendees(path: &Path, atts: &[Attendee]) -> std::io::Result<()> {
+    println!("Saving {} attendees at {}", atts.len(), path.display());
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["NOME", "CPF", "TIPO_ID", "CH", "FUNCAO"])?;
+    for att in atts {
+        wtr.write_record([
+            att.name.as_str(),
+            att.id.as_str(),
+            att.id.kind().as_str(),
+            &att.workload.hm(),
+            att.role.as_str(),
+        ])?;
+    }
+    wtr.flush()
+}
+
+fn main() -> std::io::Result<()> {
+    let cli = Cli::parse();
+    let config = Config::load(cli.config.as_deref())
+        .unwrap_or_else(|err| panic!("Error while reading the configuration file: {err}"));
+    let orgs = Org::all(&config).unwrap_or_else(|err| panic!("Invalid configuration: {err}"));
+    let org_names = orgs
+        .iter()
+        .map(|org| org.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let org = cli.org.as_deref().map(|name| {
+        orgs.iter()
+            .find(|org| org.name == name)
+            .unwrap_or_else(|| panic!("Unknown organization {name}, expected one of: {org_names}"))
+    });
+    // the SQL of a shared installation must not go to the wrong database
+    let writes_sql = matches!(
+        cli.command,
+        None | Some(Command::Correct(_) | Command::Revoke(_))
+    );
+    if writes_sql && org.is_none() && !orgs.is_empty() {
+        panic!("Choose the organization with --org: {org_names}");
+    }
+    let profile = match org {
+        Some(org) => org.settings.clone(),
+        None => config
+            .profile(cli.profile.as_deref())
+            .unwrap_or_else(|err| panic!("Invalid configuration: {err}")),
+    };
+    let org_ledger = org.and_then(|org| org.ledger.as_deref());
+    match (cli.command, cli.args) {
+        (Some(Command::Render(args)), _) => cmd::render::run(args, &profile, org),
+        (Some(Command::Verify(args)), _) => cmd::verify::run(args, &profile, org_ledger),
+        (Some(Command::CheckCpf(args)), _) => cmd::check_cpf::run(args),
+        (Some(Command::Attendance(args)), _) => cmd::attendance::run(args),
+        (Some(Command::Meeting(args)), _) => cmd::meeting::run(args),
+        (Some(Command::Reconcile(args)), _) => cmd::reconcile::run(args),
+        (Some(Command::Correct(args)), _) => cmd::correct::run(args, &profile),
+        (Some(Command::Revoke(args)), _) => cmd::revoke::run(args, &profile, org_ledger),
+        (Some(Command::Credentials(args)), _) => cmd::credentials::run(args, &profile),
+        (Some(Command::History(args)), _) => cmd::history::run(args, &profile.history),
+        (None, Some(args)) => write_sql(args, &profile, org),
+        (None, None) => unreachable!("SQL arguments are required without a subcommand"),
+    }
+}
+
+fn write_sql(args: Args, profile: &Profile, org: Option<&Org>) -> std::io::Result<()> {
+    let schema = &profile.schema;
+    let img_name = match (&args.cert_img, &args.upload_img) {
+        (Some(img), None) | (None, Some(img)) => Some(
+            img.file_name()
+                .expect("Error while getting image file name")
+                .to_str()
+                .expect("Thi image name is not a valid UTF-8")
+                .split_whitespace()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+        ),
+        (None, None) => None,
+        _ => unreachable!("Both args should not be provided at the same time"),
+    };
+
+    let input_hash = input_hash(&[&args.event, &args.attendees])?;
+    let evt = read_event(&args.event, profile, org)?;
+    let atts = read_attendees(
+        &args.attendees,
+        name_case(args.keep_name_case),
+        Dedup {
+            workload: args.duplicate_hours,
+            name: args.duplicate_names,
+        },
+    )?;
+
+    let template = match &args.sql_template {
+        Some(path) => Some(
+            std::fs::read_to_string(path)?
+                .parse::<Template>()
+                .unwrap_or_else(|err| panic!("Error while parsing the SQL template: {err}")),
+        ),
+        None => None,
+    };
+
+    let mut event = evt.into_event(atts);
+    round(&mut event, args.rounding.unwrap_or(profile.rounding));
+    validate(&event, img_name.as_deref(), args.strict || profile.strict);
+    if args.codes {
+        event = event.sign(signer(profile)?);
+    }
+    let ledger = match (&args.ledger, org) {
+        (Some(path), _) => Some(path.as_path()),
+        (None, Some(org)) if args.codes => org.ledger.as_deref(),
+        (None, _) => None,
+    };
+    if let Some(path) = ledger {
+        println!("Recording issued certificates at {}", path.display());
+        let mut ledger = match std::fs::File::open(path) {
+            Ok(file) => Ledger::read(std::io::BufReader::new(file))
+                .expect("Error while parsing the ledger file"),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ledger::new(),
+            Err(err) => return Err(err),
+        };
+        ledger.add_many(event.issued());
+        ledger
+            .write(std::fs::File::create(path)?)
+            .expect("failed to write into ledger file");
+    }
+    let img_path = img_name
+        .as_ref()
+        .map(|name| format!("{}{name}", profile.img_url))
+        .unwrap_or_default();
+    let cert = event.into_cert(img_path);
+    let pool = match &template {
+        Some(template) => cert
+            .render_sql(template, schema)
+            .unwrap_or_else(|err| panic!("Error while rendering the SQL template: {err}")),
+        None => cert.to_sql_with(schema),
+    };
+    let queries = pool.into_req(schema.database()).to_string();
 
     println!("Saving SQL queries at {}", args.output.display());
-    std::fs::File::create(args.output)
+    std::fs::File::create(&args.output)
         .expect("failed to create output file")
         .write_all(queries.as_bytes())
         .expect("failed to write into output file");
 
+    let (mut history, source) = open_history(profile, input_hash, &args.output);
+    let import = history
+        .record(&cert, &source)
+        .expect("failed to write into the history");
+    println!("Recorded import #{import} at {}", profile.history.display());
+
     if let Some(img) = args.upload_img {
+        // the secrets override the profile
+        let secrets = Secrets::load(profile)?;
+        let addr = secrets
+            .get("SFTP_ADDRESS")
+            .or_else(|| profile.sftp_address.clone())
+            .expect("SFTP_ADDRESS not found in the credentials or environment");
+        let user = secrets
+            .get("SFTP_USER")
+            .or_else(|| profile.sftp_user.clone())
+            .expect("SFTP_USER not found in the credentials or environment");
+
         print!("Uploading event image...");
         std::io::stdout().flush()?;
 
-        // Verify environment variables.
-        let _ = dotenvy::dotenv();
-        let addr =
-            std::env::var("SFTP_ADDRESS").expect("SFTP_ADDRESS environment variable not found");
-        let user = std::env::var("SFTP_USER").expect("SFTP_USER environment variable not found");
-        let pwd = std::env::var("SFTP_PWD").expect("SFTP_PWD environment variable not found");
-
         // Upload event image to the SFTP server
-        let conn = pet_scroll::sftp::connect(addr, &user, &pwd)?;
-        let remote_path = format!("./certificados/img/{img_name}");
-        pet_scroll::sftp::upload(&conn, img, remote_path)?;
+        let pwd;
+        let auth = match &profile.sftp_auth {
+            SftpAuth::Password => {
+                pwd = secrets
+                    .get("SFTP_PWD")
+                    .expect("SFTP_PWD not found in the credentials or environment");
+                Auth::Password(&pwd)
+            }
+            SftpAuth::Agent => Auth::Agent,
+            SftpAuth::Key(key) => Auth::Key(key),
+        };
+        let img_name = img_name.expect("the uploaded image has a name");
+        let remote_path = format!("{}/{img_name}", profile.img_dir.trim_end_matches('/'));
+        let uploaded = pet_scroll::sftp::connect_with(addr, &user, auth)
+            .and_then(|conn| pet_scroll::sftp::upload(&conn, img, remote_path));
+        let result = match &uploaded {
+            Ok(()) => "ok".to_owned(),
+            Err(err) => err.to_string(),
+        };
+        history
+            .set_upload(import, &result)
+            .expect("failed to write into the history");
+        uploaded?;
         println!(" Done!")
     }
     Ok(())
@@ -0,0 +1,194 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::{
+    cert::csv_data::{EventData, EventDesc, ParseError},
+    config::{CatalogText, Config, ConfigError, Profile, Settings},
+};
+
+/// An organization sharing the installation, like a PET group, read from an
+/// `[orgs.<name>]` section with the settings of a profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Org {
+    pub name: String,
+    pub settings: Profile,
+    /// Certificate texts of `[orgs.<name>.texts]`, used in the event files
+    /// as `@name`.
+    pub texts: BTreeMap<String, EventDesc>,
+    /// Ledger of the certificates issued by the organization.
+    pub ledger: Option<PathBuf>,
+}
+
+impl Org {
+    /// Every organization of the configuration, where no two organizations
+    /// may share a database, a ledger or a history.
+    pub fn all(config: &Config) -> Result<Vec<Self>, ConfigError> {
+        let mut orgs = Vec::<Self>::new();
+        for (name, settings) in &config.orgs {
+            let org = Self::read(config, name, settings)?;
+            let section = format!("orgs.{name}");
+            for other in &orgs {
+                if other.settings.schema.database() == org.settings.schema.database() {
+                    let msg = format!("same database as the {} organization", other.name);
+                    return Err(ConfigError::invalid(
+                        &format!("{section}.schema"),
+                        "database",
+                        msg,
+                    ));
+                }
+                if other.ledger.is_some() && other.ledger == org.ledger {
+                    let msg = format!("same ledger as the {} organization", other.name);
+                    return Err(ConfigError::invalid(&section, "ledger", msg));
+                }
+                if other.settings.history == org.settings.history {
+                    let msg = format!("same history as the {} organization", other.name);
+                    return Err(ConfigError::invalid(&section, "history", msg));
+                }
+            }
+            orgs.push(org);
+        }
+        Ok(orgs)
+    }
+
+    fn read(config: &Config, name: &str, settings: &Settings) -> Result<Self, ConfigError> {
+        let section = format!("orgs.{name}");
+        let mut profile = Profile::read(config, name, settings, &section)?;
+        if settings.history.is_none() {
+            profile.history = PathBuf::from(format!("pet-scroll-{name}.db"));
+        }
+
+        let section = format!("{section}.texts");
+        let mut texts = BTreeMap::new();
+        for (key, text) in &settings.texts {
+            let desc = match text {
+                CatalogText::Text(text) => EventDesc::parse(text.clone(), &profile.placeholders)
+                    .map_err(|err| ConfigError::invalid(&section, key, err.to_string()))?,
+                CatalogText::Id(id) => u32::try_from(*id)
+                    .map(EventDesc::Id)
+                    .map_err(|_| ConfigError::invalid(&section, key, "invalid text id"))?,
+            };
+            texts.insert(key.clone(), desc);
+        }
+
+        Ok(Self {
+            name: name.to_owned(),
+            settings: profile,
+            texts,
+            ledger: settings.ledger.clone(),
+        })
+    }
+
+    /// Replace the `@name` texts of the event with the ones of the catalog.
+    pub fn resolve_texts(&self, data: &mut EventData) -> Result<(), ParseError<String>> {
+        for desc in std::iter::once(&mut data.desc).chain(data.roles.values_mut()) {
+            let Some(name) = desc.catalog_name() else {
+                continue;
+            };
+            *desc =
+                self.texts.get(name).cloned().ok_or_else(|| {
+                    ParseError::new("a text of the organization", format!("@{name}"))
+                })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::csv_data::{EventDate, Role};
+    use time::macros::date;
+
+    const SRC: &str = "[schema]\n\
+                       \"usuario.identificacao\" = \"cpf\"\n\
+                       [orgs.petcomp]\n\
+                       ledger = \"petcomp.csv\"\n\
+                       [orgs.petcomp.texts]\n\
+                       palestra = \"#nome# palestrou no #evento#\"\n\
+                       minicurso = 12\n\
+                       [orgs.petsi]\n\
+                       img_dir = \"./petsi/img\"\n\
+                       [orgs.petsi.schema]\n\
+                       database = \"petsi\"\n";
+
+    #[test]
+    fn read_orgs() {
+        let orgs = Org::all(&Config::parse(SRC).expect("valid config")).expect("valid orgs");
+        assert_eq!(2, orgs.len());
+        let (petcomp, petsi) = (&orgs[0], &orgs[1]);
+        assert_eq!("petcomp", petcomp.name);
+        assert_eq!("petcomp", petcomp.settings.schema.database());
+        assert_eq!(Some(PathBuf::from("petcomp.csv")), petcomp.ledger);
+        assert_eq!(Some(&EventDesc::Id(12)), petcomp.texts.get("minicurso"));
+        assert_eq!("petsi", petsi.settings.schema.database());
+        assert_eq!(
+            ["cpf"],
+            petsi.settings.schema.columns("usuario", ["identificacao"])
+        );
+        assert_eq!("./petsi/img", petsi.settings.img_dir);
+        assert_eq!(
+            PathBuf::from("pet-scroll-petcomp.db"),
+            petcomp.settings.history
+        );
+        assert_eq!(PathBuf::from("pet-scroll-petsi.db"), petsi.settings.history);
+        assert!(petsi.texts.is_empty());
+
+        let src = "[orgs.a]\nplaceholders = [\"curso\"]\n[orgs.a.texts]\nx = \"#nome# do #curso#\"";
+        let orgs = Org::all(&Config::parse(src).unwrap()).expect("known placeholder");
+        assert!(orgs[0].texts.contains_key("x"));
+
+        for src in [
+            "[orgs.a]\n[orgs.b]",
+            "[orgs.a]\nledger = \"l.csv\"\n[orgs.b]\nledger = \"l.csv\"\n[orgs.b.schema]\ndatabase = \"b\"",
+            "[orgs.a]\nhistory = \"h.db\"\n[orgs.b]\nhistory = \"h.db\"\n[orgs.b.schema]\ndatabase = \"b\"",
+            "[orgs.a]\nledger = 1",
+            "[orgs.a]\nhost = \"pet.example\"",
+            "[orgs.a]\n[orgs.a.texts]\nx = \"#nada#\"",
+            "[orgs.a]\n[orgs.a.texts]\nx = -1",
+            "[orgs.a]\n[orgs.a.texts]\nx = true",
+        ] {
+            let orgs = Config::parse(src).and_then(|config| Org::all(&config));
+            assert!(orgs.is_err(), "{src}");
+        }
+    }
+
+    #[test]
+    fn resolve_texts() {
+        let orgs = Org::all(&Config::parse(SRC).expect("valid config")).expect("valid orgs");
+        let mut data = EventData {
+            id: None,
+            name: "Event".to_owned(),
+            desc: EventDesc::Text("@minicurso".to_owned()),
+            date: EventDate::Day(date!(2023 - 5 - 4)),
+            roles: BTreeMap::from([
+                (Role::Speaker, EventDesc::Text("@palestra".to_owned())),
+                (
+                    Role::Monitor,
+                    EventDesc::Text("#nome# monitorou".to_owned()),
+                ),
+            ]),
+            max_workload: None,
+        };
+        let mut other = data.clone();
+        let issues = data
+            .clone()
+            .into_event(Vec::new())
+            .validate(Some("cert.png"));
+        assert!(issues.iter().any(
+            |issue| issue.message == "The event text @minicurso needs an organization catalog"
+        ));
+
+        orgs[0]
+            .resolve_texts(&mut data)
+            .expect("texts in the catalog");
+        assert_eq!(EventDesc::Id(12), data.desc);
+        assert_eq!(
+            EventDesc::Text("#nome# palestrou no #evento#".to_owned()),
+            data.roles[&Role::Speaker]
+        );
+        assert_eq!(
+            EventDesc::Text("#nome# monitorou".to_owned()),
+            data.roles[&Role::Monitor]
+        );
+        assert!(orgs[1].resolve_texts(&mut other).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! The DejaVu Sans font bundled in the crate.
+//!
+//! The font is embedded in the generated files with only the glyphs of their
+//! texts, so the certificates look the same in every PDF reader and can have
+//! any character of the font. Its license is in `fonts/LICENSE`.
+
+use pdf_writer::{
+    types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap},
+    Filter, Finish, Name, Pdf, Rect, Ref, Str,
+};
+use std::{collections::BTreeMap, sync::OnceLock};
+use ttf_parser::{Face, GlyphId};
+
+/// The bundled font file.
+const DATA: &[u8] = include_bytes!("fonts/DejaVuSans.ttf");
+
+/// PostScript name of the font.
+pub const NAME: &str = "DejaVuSans";
+
+/// Character drawn in place of the ones missing from the font.
+const REPLACEMENT: char = '?';
+
+/// Character collection of the embedded font, mapping the codes of the
+/// texts straight to glyph ids.
+const SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+fn face() -> &'static Face<'static> {
+    static FACE: OnceLock<Face<'static>> = OnceLock::new();
+    FACE.get_or_init(|| Face::parse(DATA, 0).expect("valid bundled font"))
+}
+
+/// Glyph drawing `c`, or the replacement one if the font does not have it.
+fn glyph(c: char) -> GlyphId {
+    let face = face();
+    face.glyph_index(c)
+        .or_else(|| face.glyph_index(REPLACEMENT))
+        .unwrap_or(GlyphId(0))
+}
+
+/// Convert font units to units of 1/1000 of the font size.
+fn to_pdf_units(units: impl Into<f32>) -> f32 {
+    units.into() * 1000.0 / f32::from(face().units_per_em())
+}
+
+/// Width of a glyph in units of 1/1000 of the font size.
+fn glyph_width(glyph: GlyphId) -> f32 {
+    to_pdf_units(face().glyph_hor_advance(glyph).unwrap_or_default())
+}
+
+/// Width of `text` in points when written with the given font `size`.
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let units: f32 = text.chars().map(|c| glyph_width(glyph(c))).sum();
+    units * size / 1000.0
+}
+
+/// Encode `text` as the two byte glyph ids expected by the embedded font.
+pub fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .flat_map(|c| glyph(c).0.to_be_bytes())
+        .collect()
+}
+
+/// Characters of the texts of a document, by the glyph drawing them.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Glyphs {
+    chars: BTreeMap<u16, char>,
+}
+
+impl Glyphs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the characters of `text`.
+    pub fn add(&mut self, text: &str) {
+        for c in text.chars() {
+            let id = glyph(c).0;
+            // keep the character of the font over the replaced ones
+            if face().glyph_index(c).is_some() || !self.chars.contains_key(&id) {
+                self.chars.insert(id, c);
+            }
+        }
+    }
+
+    /// Prefix of the font name telling the readers it is a subset, derived
+    /// from the glyphs so equal subsets get the same name.
+    fn tag(&self) -> String {
+        let hash = self.chars.keys().fold(0x811C_9DC5_u32, |hash, &id| {
+            (hash ^ u32::from(id)).wrapping_mul(0x0100_0193)
+        });
+        (0..6)
+            .map(|i| char::from(b'A' + ((hash >> (i * 5)) % 26) as u8))
+            .collect()
+    }
+
+    /// Write the font subset with these glyphs, where `refs` are the ids of
+    /// the font, its CID font, font descriptor, font file and ToUnicode map.
+    pub fn write(&self, pdf: &mut Pdf, refs: [Ref; 5]) {
+        let [font_id, cid_id, descriptor_id, file_id, cmap_id] = refs;
+        let face = face();
+        let name = format!("{}+{NAME}", self.tag());
+
+        pdf.type0_font(font_id)
+            .base_font(Name(name.as_bytes()))
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_id)
+            .to_unicode(cmap_id);
+
+        let mut cid = pdf.cid_font(cid_id);
+        cid.subtype(CidFontType::Type2)
+            .base_font(Name(name.as_bytes()))
+            .system_info(SYSTEM_INFO)
+            .font_descriptor(descriptor_id)
+            .cid_to_gid_map_predefined(Name(b"Identity"));
+        let mut widths = cid.widths();
+        for &id in self.chars.keys() {
+            widths.consecutive(id, [glyph_width(GlyphId(id))]);
+        }
+        widths.finish();
+        cid.finish();
+
+        let bbox = face.global_bounding_box();
+        let weight = f32::from(face.weight().to_number());
+        pdf.font_descriptor(descriptor_id)
+            .name(Name(name.as_bytes()))
+            .flags(FontFlags::SYMBOLIC)
+            .bbox(Rect::new(
+                to_pdf_units(bbox.x_min),
+                to_pdf_units(bbox.y_min),
+                to_pdf_units(bbox.x_max),
+                to_pdf_units(bbox.y_max),
+            ))
+            .italic_angle(face.italic_angle())
+            .ascent(to_pdf_units(face.ascender()))
+            .descent(to_pdf_units(face.descender()))
+            .cap_height(to_pdf_units(
+                face.capital_height().unwrap_or(face.ascender()),
+            ))
+            // usual estimate of the stem width from the weight
+            .stem_v(10.0 + 0.244 * (weight - 50.0))
+            .font_file2(file_id);
+
+        let glyphs = std::iter::once(0)
+            .chain(self.chars.keys().copied())
+            .collect::<Vec<_>>();
+        let subset = subsetter::subset(DATA, 0, subsetter::Profile::pdf(&glyphs))
+            .expect("the bundled font can be subset");
+        let subset = miniz_oxide::deflate::compress_to_vec_zlib(&subset, 6);
+        pdf.stream(file_id, &subset).filter(Filter::FlateDecode);
+
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+        for (&id, &c) in &self.chars {
+            cmap.pair(id, c);
+        }
+        pdf.cmap(cmap_id, &cmap.finish());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width() {
+        assert_eq!(0.0, text_width("", 12.0));
+        let a = text_width("a", 10.0);
+        assert!(a > 5.0 && a < 7.0, "{a}");
+        assert_eq!(text_width("Angela", 10.0), text_width("Ângela", 10.0));
+        assert!(text_width("—", 10.0) > text_width("–", 10.0));
+        assert_eq!(text_width("?", 10.0), text_width("\u{E000}", 10.0));
+    }
+
+    #[test]
+    fn encoding() {
+        let ids = |text: &str| {
+            encode(text)
+                .chunks(2)
+                .map(|id| u16::from_be_bytes([id[0], id[1]]))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(8, encode("João").len());
+        assert_eq!(ids("o"), ids("João")[1..2]);
+        assert_ne!(ids("a"), ids("ã"));
+        assert_eq!(ids("?"), ids("\u{E000}"));
+        assert!(ids("“A” – ’ €…—").iter().all(|&id| id != ids("?")[0]));
+    }
+
+    #[test]
+    fn subset_glyphs() {
+        let mut glyphs = Glyphs::new();
+        glyphs.add("Ana ?");
+        glyphs.add("\u{E000}");
+        assert_eq!(5, glyphs.chars.len());
+        assert_eq!(Some(&'?'), glyphs.chars.get(&glyph('?').0));
+
+        let mut other = Glyphs::new();
+        other.add("? anA");
+        assert_eq!(glyphs.tag(), other.tag());
+        assert!(glyphs.tag().chars().all(|c| c.is_ascii_uppercase()));
+    }
+}
@@ -0,0 +1,526 @@
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use qrcode::{types::QrError, Color, QrCode};
+use std::{fs::File, io::BufReader, path::Path, str::FromStr};
+
+use crate::cert::csv_data::ParseError;
+
+pub mod font;
+
+/// Points per millimetre.
+const PT_PER_MM: f32 = 72.0 / 25.4;
+
+/// Width of the generated pages in millimetres (landscape A4).
+const PAGE_WIDTH_MM: f32 = 297.0;
+
+/// Smallest font size used when shrinking a text to fit its box.
+const MIN_FONT_SIZE: f32 = 6.0;
+
+/// Distance between lines relative to the font size.
+const LINE_HEIGHT: f32 = 1.3;
+
+/// A region of the page where a text is written.
+///
+/// Positions and sizes are in millimetres measured from the top left corner
+/// of the page. The font size is in points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub font_size: f32,
+}
+
+impl FromStr for TextBox {
+    type Err = ParseError<String>;
+
+    /// Parse a text box from `x,y,width,height[,font_size]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseError::new("x,y,width,height[,font_size]", s.to_owned());
+        let vals = s
+            .split(',')
+            .map(|val| val.trim().parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| err())?;
+
+        match vals[..] {
+            [x, y, width, height] => Ok(Self {
+                x,
+                y,
+                width,
+                height,
+                ..Layout::default().body
+            }),
+            [x, y, width, height, font_size] => Ok(Self {
+                x,
+                y,
+                width,
+                height,
+                font_size,
+            }),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// A square region of the page where the QR code is drawn.
+///
+/// Position and size are in millimetres measured from the top left corner
+/// of the page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QrBox {
+    pub x: f32,
+    pub y: f32,
+    pub size: f32,
+}
+
+impl FromStr for QrBox {
+    type Err = ParseError<String>;
+
+    /// Parse a QR code box from `x,y,size`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseError::new("x,y,size", s.to_owned());
+        let vals = s
+            .split(',')
+            .map(|val| val.trim().parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| err())?;
+
+        match vals[..] {
+            [x, y, size] => Ok(Self { x, y, size }),
+            _ => Err(err()),
+        }
+    }
+}
+
+/// Where each element goes on the certificate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub body: TextBox,
+    pub qr: QrBox,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            body: TextBox {
+                x: 30.0,
+                y: 75.0,
+                width: 237.0,
+                height: 70.0,
+                font_size: 16.0,
+            },
+            qr: QrBox {
+                x: 252.0,
+                y: 165.0,
+                size: 30.0,
+            },
+        }
+    }
+}
+
+/// A decoded certificate image.
+#[derive(Debug, Clone)]
+pub struct Background {
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+    alpha: Option<Vec<u8>>,
+}
+
+impl Background {
+    /// Decode the PNG image at `path`.
+    pub fn open<P>(path: P) -> std::io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+
+        let (rgb, alpha) = match info.color_type {
+            png::ColorType::Rgb => (buffer, None),
+            png::ColorType::Rgba => {
+                let rgb = buffer
+                    .chunks_exact(4)
+                    .flat_map(|px| &px[..3])
+                    .copied()
+                    .collect();
+                let alpha = buffer.chunks_exact(4).map(|px| px[3]).collect();
+                (rgb, Some(alpha))
+            }
+            png::ColorType::Grayscale => (buffer.iter().flat_map(|&g| [g; 3]).collect(), None),
+            png::ColorType::GrayscaleAlpha => {
+                let rgb = buffer.chunks_exact(2).flat_map(|px| [px[0]; 3]).collect();
+                let alpha = buffer.chunks_exact(2).map(|px| px[1]).collect();
+                (rgb, Some(alpha))
+            }
+            png::ColorType::Indexed => unreachable!("palettes are expanded by the decoder"),
+        };
+
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            rgb,
+            alpha,
+        })
+    }
+
+    /// Page size in points keeping the image aspect ratio.
+    fn page_size(&self) -> (f32, f32) {
+        let width = PAGE_WIDTH_MM * PT_PER_MM;
+        (width, width * self.height as f32 / self.width as f32)
+    }
+}
+
+/// Split `text` into lines that fit in `width` points.
+fn wrap(text: &str, width: f32, size: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_owned()
+            } else {
+                format!("{line} {word}")
+            };
+            if line.is_empty() || font::text_width(&candidate, size) <= width {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_owned()));
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Modules of a QR code, row by row.
+#[derive(Debug, Clone, PartialEq)]
+struct Modules {
+    width: usize,
+    dark: Vec<bool>,
+}
+
+/// A single certificate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    text: String,
+    qr: Option<Modules>,
+}
+
+impl Page {
+    pub fn new(text: String) -> Self {
+        Self { text, qr: None }
+    }
+
+    /// Draw a QR code with the given `data`.
+    pub fn qr(mut self, data: &str) -> Result<Self, QrError> {
+        let code = QrCode::new(data)?;
+        self.qr = Some(Modules {
+            width: code.width(),
+            dark: code
+                .to_colors()
+                .into_iter()
+                .map(|color| color == Color::Dark)
+                .collect(),
+        });
+        Ok(self)
+    }
+}
+
+impl From<String> for Page {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// A PDF document with one certificate per page.
+#[derive(Debug)]
+pub struct Document<'a> {
+    background: &'a Background,
+    layout: &'a Layout,
+    pages: Vec<Page>,
+}
+
+impl<'a> Document<'a> {
+    pub fn new(background: &'a Background, layout: &'a Layout) -> Self {
+        Self {
+            background,
+            layout,
+            pages: Vec::new(),
+        }
+    }
+
+    /// Add a certificate page.
+    pub fn page<P>(mut self, page: P) -> Self
+    where
+        P: Into<Page>,
+    {
+        self.pages.push(page.into());
+        self
+    }
+
+    /// Add a certificate page.
+    pub fn add<P>(&mut self, page: P)
+    where
+        P: Into<Page>,
+    {
+        self.pages.push(page.into());
+    }
+
+    /// Draw the QR code modules over a white square.
+    fn write_qr(&self, content: &mut Content, qr: &Modules, page_height: f32) {
+        let QrBox { x, y, size } = self.layout.qr;
+        let (x, size) = (x * PT_PER_MM, size * PT_PER_MM);
+        let bottom = page_height - y * PT_PER_MM - size;
+
+        let modules = qr.width;
+        // one module of quiet zone on each side
+        let module = size / (modules + 2) as f32;
+
+        content.save_state();
+        content.set_fill_gray(1.0);
+        content.rect(x, bottom, size, size);
+        content.fill_nonzero();
+        content.set_fill_gray(0.0);
+        for (i, &dark) in qr.dark.iter().enumerate() {
+            if dark {
+                let (col, row) = (i % modules, i / modules);
+                let mx = x + (col + 1) as f32 * module;
+                let my = bottom + size - (row + 2) as f32 * module;
+                content.rect(mx, my, module, module);
+            }
+        }
+        content.fill_nonzero();
+        content.restore_state();
+    }
+
+    /// Write the text box content of a page.
+    fn write_text(&self, content: &mut Content, text: &str, page_height: f32) {
+        let TextBox {
+            x,
+            y,
+            width,
+            height,
+            font_size,
+        } = self.layout.body;
+        let (x, width, height) = (x * PT_PER_MM, width * PT_PER_MM, height * PT_PER_MM);
+        let top = page_height - y * PT_PER_MM;
+
+        // shrink the text until it fits in the box
+        let mut size = font_size;
+        let mut lines = wrap(text, width, size);
+        while size > MIN_FONT_SIZE && lines.len() as f32 * size * LINE_HEIGHT > height {
+            size -= 0.5;
+            lines = wrap(text, width, size);
+        }
+
+        let leading = size * LINE_HEIGHT;
+        let block = lines.len() as f32 * leading;
+        // first baseline of the vertically centered block
+        let mut baseline = top - (height - block).max(0.0) / 2.0 - size;
+
+        content.begin_text();
+        content.set_font(Name(b"F1"), size);
+        for line in &lines {
+            let offset = (width - font::text_width(line, size)) / 2.0;
+            content.set_text_matrix([1.0, 0.0, 0.0, 1.0, x + offset, baseline]);
+            content.show(Str(&font::encode(line)));
+            baseline -= leading;
+        }
+        content.end_text();
+    }
+
+    /// Build the PDF file.
+    pub fn finish(self) -> Vec<u8> {
+        let catalog_id = Ref::new(1);
+        let tree_id = Ref::new(2);
+        let font_id = Ref::new(3);
+        let image_id = Ref::new(4);
+        let mask_id = Ref::new(5);
+        let first_page = 6;
+
+        let (width, height) = self.background.page_size();
+        let page_ids = (0..self.pages.len() as i32)
+            .map(|i| Ref::new(first_page + 2 * i))
+            .collect::<Vec<_>>();
+        // the other font objects go after the pages
+        let font_parts = first_page + 2 * page_ids.len() as i32;
+        let font_refs = [
+            font_id,
+            Ref::new(font_parts),
+            Ref::new(font_parts + 1),
+            Ref::new(font_parts + 2),
+            Ref::new(font_parts + 3),
+        ];
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(tree_id);
+        pdf.pages(tree_id)
+            .kids(page_ids.iter().copied())
+            .count(page_ids.len() as i32);
+        let mut glyphs = font::Glyphs::new();
+        for cert in &self.pages {
+            glyphs.add(&cert.text);
+        }
+        glyphs.write(&mut pdf, font_refs);
+
+        let bg = self.background;
+        let rgb = miniz_oxide::deflate::compress_to_vec_zlib(&bg.rgb, 6);
+        let mut image = pdf.image_xobject(image_id, &rgb);
+        image.filter(Filter::FlateDecode);
+        image.width(bg.width as i32);
+        image.height(bg.height as i32);
+        image.color_space().device_rgb();
+        image.bits_per_component(8);
+        if bg.alpha.is_some() {
+            image.s_mask(mask_id);
+        }
+        image.finish();
+
+        if let Some(alpha) = &bg.alpha {
+            let alpha = miniz_oxide::deflate::compress_to_vec_zlib(alpha, 6);
+            let mut mask = pdf.image_xobject(mask_id, &alpha);
+            mask.filter(Filter::FlateDecode);
+            mask.width(bg.width as i32);
+            mask.height(bg.height as i32);
+            mask.color_space().device_gray();
+            mask.bits_per_component(8);
+        }
+
+        for (page_id, cert) in page_ids.iter().zip(&self.pages) {
+            let content_id = Ref::new(page_id.get() + 1);
+
+            let mut page = pdf.page(*page_id);
+            page.parent(tree_id)
+                .media_box(Rect::new(0.0, 0.0, width, height))
+                .contents(content_id);
+            let mut resources = page.resources();
+            resources.x_objects().pair(Name(b"Im1"), image_id);
+            resources.fonts().pair(Name(b"F1"), font_id);
+            resources.finish();
+            page.finish();
+
+            let mut content = Content::new();
+            content.save_state();
+            content.transform([width, 0.0, 0.0, height, 0.0, 0.0]);
+            content.x_object(Name(b"Im1"));
+            content.restore_state();
+            self.write_text(&mut content, &cert.text, height);
+            if let Some(qr) = &cert.qr {
+                self.write_qr(&mut content, qr, height);
+            }
+            pdf.stream(content_id, &content.finish());
+        }
+
+        pdf.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_text_box() {
+        let default = Layout::default().body;
+        assert_eq!(
+            Ok(TextBox {
+                x: 10.0,
+                y: 20.0,
+                width: 100.0,
+                height: 50.5,
+                ..default
+            }),
+            "10, 20, 100, 50.5".parse::<TextBox>()
+        );
+        assert_eq!(
+            Ok(TextBox {
+                x: 10.0,
+                y: 20.0,
+                width: 100.0,
+                height: 50.0,
+                font_size: 12.0
+            }),
+            "10,20,100,50,12".parse::<TextBox>()
+        );
+        assert!("10,20,100".parse::<TextBox>().is_err());
+        assert!("10,20,a,50".parse::<TextBox>().is_err());
+    }
+
+    #[test]
+    fn wrap_text() {
+        let size = 10.0;
+        let text = "Certificamos que Fulano participou do evento";
+        let width = font::text_width("Certificamos que Fulano", size);
+        let lines = wrap(text, width, size);
+
+        assert_eq!(
+            vec!["Certificamos que Fulano", "participou do evento"],
+            lines
+        );
+        assert_eq!(vec!["Uma", "linha"], wrap("Uma\nlinha", 1000.0, size));
+        assert_eq!(vec!["Certificamos"], wrap("Certificamos", 1.0, size));
+    }
+
+    #[test]
+    fn build_document() {
+        let background = Background {
+            width: 2,
+            height: 1,
+            rgb: vec![255; 6],
+            alpha: None,
+        };
+        let layout = Layout::default();
+        let pdf = Document::new(&background, &layout)
+            .page("Certificamos que A participou".to_owned())
+            .page("Certificamos que B participou".to_owned())
+            .finish();
+
+        assert!(pdf.starts_with(b"%PDF-"));
+        let pdf = String::from_utf8_lossy(&pdf);
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("+DejaVuSans"));
+        assert!(pdf.contains("/Encoding /Identity-H"));
+        assert!(pdf.contains("/FontFile2"));
+        assert!(pdf.contains("/ToUnicode"));
+    }
+
+    #[test]
+    fn parse_qr_box() {
+        assert_eq!(
+            Ok(QrBox {
+                x: 250.0,
+                y: 160.0,
+                size: 25.5
+            }),
+            "250, 160, 25.5".parse::<QrBox>()
+        );
+        assert!("250,160".parse::<QrBox>().is_err());
+    }
+
+    #[test]
+    fn document_with_qr() {
+        let background = Background {
+            width: 2,
+            height: 1,
+            rgb: vec![255; 6],
+            alpha: None,
+        };
+        let layout = Layout::default();
+        let page = Page::new("Certificamos que A participou".to_owned())
+            .qr("https://example.com/verificar?codigo=ABCD")
+            .expect("valid QR code data");
+        let plain = Document::new(&background, &layout)
+            .page("Certificamos que A participou".to_owned())
+            .finish();
+        let with_qr = Document::new(&background, &layout).page(page).finish();
+
+        assert!(with_qr.len() > plain.len());
+        assert!(String::from_utf8_lossy(&with_qr).contains(" re\n"));
+    }
+}
@@ -6,8 +6,26 @@ use std::{
     path::Path,
 };
 
+/// How to authenticate to the SFTP server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Auth<'a> {
+    Password(&'a str),
+    /// Keys of the running SSH agent.
+    Agent,
+    /// Private key file.
+    Key(&'a Path),
+}
+
 /// Creates a new SFTP connection.
 pub fn connect<A>(addr: A, user: &str, pwd: &str) -> std::io::Result<Sftp>
+where
+    A: ToSocketAddrs,
+{
+    connect_with(addr, user, Auth::Password(pwd))
+}
+
+/// Creates a new SFTP connection with the given authentication.
+pub fn connect_with<A>(addr: A, user: &str, auth: Auth) -> std::io::Result<Sftp>
 where
     A: ToSocketAddrs,
 {
@@ -15,7 +33,11 @@ where
     let mut sess = Session::new()?;
     sess.set_tcp_stream(tcp);
     sess.handshake()?;
-    sess.userauth_password(user, pwd)?;
+    match auth {
+        Auth::Password(pwd) => sess.userauth_password(user, pwd)?,
+        Auth::Agent => sess.userauth_agent(user)?,
+        Auth::Key(key) => sess.userauth_pubkey_file(user, None, key, None)?,
+    }
 
     sess.sftp().map_err(std::io::Error::from)
 }
@@ -1,5 +1,143 @@
+use std::collections::BTreeMap;
+
+use crate::config::{Config, ConfigError, Names};
+
 pub trait ToSQL {
-    fn to_sql(&self) -> QueryPool;
+    /// Queries using the names of the given database schema.
+    fn to_sql_with(&self, schema: &Schema) -> QueryPool;
+
+    /// Queries using the default database schema.
+    fn to_sql(&self) -> QueryPool {
+        self.to_sql_with(&Schema::default())
+    }
+}
+
+/// Quote a value as an SQL string literal, doubling the single quotes and
+/// escaping the backslashes, so names like `d'Ávila` keep the query valid.
+pub fn quote(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => literal.push_str("''"),
+            '\\' => literal.push_str("\\\\"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+/// Whether a name can be used unquoted in the queries: ASCII letters,
+/// digits, `_` and `$`, not only digits.
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !name.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Default database name.
+pub const DEFAULT_DATABASE: &str = "petcomp";
+
+/// Tables of the certificates database and their columns.
+const TABLES: [(&str, &[&str]); 4] = [
+    ("evento", &["id", "nome", "data", "img"]),
+    ("texto", &["id", "texto"]),
+    ("usuario", &["id", "nome", "identificacao"]),
+    (
+        "participacao",
+        &["usuario", "evento", "texto", "ch", "codigo", "revogado"],
+    ),
+];
+
+/// Names of the database, tables and columns used in the queries, for sites
+/// with a schema different from the default one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    database: String,
+    /// Actual names by default name, like `evento` or `evento.nome`.
+    names: BTreeMap<String, String>,
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        Self {
+            database: DEFAULT_DATABASE.to_owned(),
+            names: BTreeMap::new(),
+        }
+    }
+}
+
+impl Schema {
+    /// Read the `[schema]` section of the configuration, where `database`
+    /// names the database and the other keys rename a table, like
+    /// `evento = "eventos"`, or a column, like `"evento.nome" = "titulo"`.
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut schema = Self::default();
+        schema.apply(&config.schema, "schema")?;
+        Ok(schema)
+    }
+
+    /// Override the names with the ones of a schema section.
+    pub(crate) fn apply(&mut self, names: &Names, section: &str) -> Result<(), ConfigError> {
+        for (key, value) in names {
+            if !is_identifier(value) {
+                let msg = "expected a name of letters, digits, _ or $";
+                return Err(ConfigError::invalid(section, key, msg));
+            }
+            if key == "database" {
+                self.database = value.to_owned();
+                continue;
+            }
+            let known = match key.split_once('.') {
+                Some((table, column)) => TABLES
+                    .iter()
+                    .any(|(name, columns)| *name == table && columns.contains(&column)),
+                None => TABLES.iter().any(|(name, _)| name == key),
+            };
+            if !known {
+                let err = ConfigError::invalid(section, key, "unknown table or column");
+                return Err(err);
+            }
+            self.names.insert(key.clone(), value.to_owned());
+        }
+        Ok(())
+    }
+
+    pub fn database(&self) -> &str {
+        &self.database
+    }
+
+    /// Every table and column, by default name, with its actual name.
+    pub fn names(&self) -> Vec<(String, &str)> {
+        let mut names = Vec::new();
+        for (table, columns) in TABLES {
+            names.push((table.to_owned(), self.table(table)));
+            for column in columns {
+                let [name] = self.columns(table, [column]);
+                names.push((format!("{table}.{column}"), name));
+            }
+        }
+        names
+    }
+
+    /// Name of a table given its default name.
+    pub fn table<'a>(&'a self, table: &'a str) -> &'a str {
+        self.names.get(table).map_or(table, String::as_str)
+    }
+
+    /// Names of the columns of a table given their default names.
+    pub fn columns<'a, const N: usize>(
+        &'a self,
+        table: &str,
+        columns: [&'a str; N],
+    ) -> [&'a str; N] {
+        columns.map(|column| {
+            self.names
+                .get(&format!("{table}.{column}"))
+                .map_or(column, String::as_str)
+        })
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -37,6 +175,38 @@ impl QueryPool {
         self.pool.extend(queries.into_iter());
     }
 
+    /// Split an SQL script into its queries at the `;` outside the string
+    /// literals and quoted names, leaving out the empty ones.
+    pub fn from_script(script: &str) -> Self {
+        let mut pool = Self::new();
+        let mut start = 0;
+        let mut quote = None;
+        let mut chars = script.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match (quote, c) {
+                (Some(_), '\\') => {
+                    chars.next();
+                }
+                (Some(q), c) if c == q => quote = None,
+                (None, '\'' | '"' | '`') => quote = Some(c),
+                (None, ';') => {
+                    pool.add_script_query(&script[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        pool.add_script_query(&script[start..]);
+        pool
+    }
+
+    fn add_script_query(&mut self, query: &str) {
+        let query = query.trim();
+        if !query.is_empty() {
+            self.pool.push(query.to_owned());
+        }
+    }
+
     pub fn into_req(self, db: &str) -> Request {
         Request::new(db).queries(self)
     }
@@ -89,6 +259,14 @@ impl std::fmt::Display for Request {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn quote_literals() {
+        assert_eq!("'Ana'", quote("Ana"));
+        assert_eq!("'d''Ávila'", quote("d'Ávila"));
+        assert_eq!("'a\\\\'' OR 1=1 -- '", quote("a\\' OR 1=1 -- "));
+    }
 
     #[test]
     fn create_pool() {
@@ -140,6 +318,22 @@ mod tests {
         assert_eq!(str, pool.to_string());
     }
 
+    #[test]
+    fn pool_from_script() {
+        let script = "SET @a := 1;\n\nINSERT INTO t VALUES ('C#; d''x', 'a\\\\');\n\
+                      SELECT `a;b` FROM t WHERE n=\"x;\"\n";
+        assert_eq!(
+            vec![
+                "SET @a := 1",
+                "INSERT INTO t VALUES ('C#; d''x', 'a\\\\')",
+                "SELECT `a;b` FROM t WHERE n=\"x;\"",
+            ],
+            QueryPool::from_script(script)
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn create_req() {
         let pool = QueryPool::new().queries(["QUERY 1".to_owned(), "QUERY 2".to_owned()]);
@@ -154,4 +348,40 @@ mod tests {
         let str = "USE database;\nQUERY 1;\nQUERY 2;\n";
         assert_eq!(str, req.to_string());
     }
+
+    #[test]
+    fn schema_config() {
+        let config = Config::parse(
+            "[schema]\n\
+             database = \"pet_db\"\n\
+             usuario = \"users\"\n\
+             \"usuario.nome\" = \"name\"\n",
+        )
+        .expect("valid config");
+        let schema = Schema::from_config(&config).expect("valid schema");
+        assert_eq!("pet_db", schema.database());
+        assert_eq!("users", schema.table("usuario"));
+        assert_eq!("evento", schema.table("evento"));
+        assert_eq!(
+            ["id", "name", "identificacao"],
+            schema.columns("usuario", ["id", "nome", "identificacao"])
+        );
+        assert_eq!(
+            Schema::default(),
+            Schema::from_config(&Config::default()).unwrap()
+        );
+
+        for src in [
+            "[schema]\nusers = \"usuario\"",
+            "[schema]\n\"usuario.name\" = \"nome\"",
+            "[schema]\nusuario = 1",
+            "[schema]\nusuario = \"\"",
+            "[schema]\nusuario = \"123\"",
+            "[schema]\nusuario = \"users; DROP TABLE evento\"",
+            "[schema]\ndatabase = \"pet`db\"",
+        ] {
+            let schema = Config::parse(src).and_then(|config| Schema::from_config(&config));
+            assert!(schema.is_err(), "{src}");
+        }
+    }
 }
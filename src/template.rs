@@ -0,0 +1,146 @@
+use minijinja::{
+    value::{Object, ObjectRepr},
+    Environment, UndefinedBehavior, Value,
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::sql::{quote, Schema};
+
+pub use minijinja::Error as TemplateError;
+
+/// Name of the template in its environment.
+const NAME: &str = "sql";
+
+/// An SQL template in the [MiniJinja](https://docs.rs/minijinja) syntax,
+/// where the `quote` filter writes a value as an SQL string literal and the
+/// indentation before a block tag and the line break after it are removed.
+///
+/// Unknown variables are errors instead of empty values.
+#[derive(Debug, Clone)]
+pub struct Template {
+    env: Environment<'static>,
+}
+
+impl std::str::FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        env.add_filter("quote", |value: String| quote(&value));
+        env.add_template_owned(NAME, src.to_owned())?;
+        Ok(Self { env })
+    }
+}
+
+impl Template {
+    /// Render the template with the variables of `ctx`.
+    pub fn render(&self, ctx: Value) -> Result<String, TemplateError> {
+        self.env.get_template(NAME)?.render(ctx)
+    }
+}
+
+/// A table of the schema, written as its name, with its columns as
+/// attributes.
+#[derive(Debug)]
+struct Table {
+    name: String,
+    columns: BTreeMap<String, String>,
+}
+
+impl Object for Table {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        self.columns.get(key.as_str()?).map(Value::from)
+    }
+
+    fn render(self: &Arc<Self>, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// The `schema` variable, with `schema.database` and the table and column
+/// names by their default names, like `schema.evento` and
+/// `schema.evento.nome`.
+pub fn schema_value(schema: &Schema) -> Value {
+    let mut tables = BTreeMap::<String, Table>::new();
+    for (name, actual) in schema.names() {
+        match name.split_once('.') {
+            Some((table, column)) => {
+                if let Some(table) = tables.get_mut(table) {
+                    table.columns.insert(column.to_owned(), actual.to_owned());
+                }
+            }
+            None => {
+                let table = Table {
+                    name: actual.to_owned(),
+                    columns: BTreeMap::new(),
+                };
+                tables.insert(name, table);
+            }
+        }
+    }
+    let mut value = tables
+        .into_iter()
+        .map(|(name, table)| (name, Value::from_object(table)))
+        .collect::<BTreeMap<_, _>>();
+    value.insert("database".to_owned(), schema.database().into());
+    Value::from(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minijinja::context;
+
+    fn ctx() -> Value {
+        context! {
+            event => context! { name => "Semana d'Ávila" },
+            codes => false,
+            atts => vec![context! { name => "A" }, context! { name => "B" }],
+            schema => schema_value(&Schema::default()),
+        }
+    }
+
+    #[test]
+    fn render_template() {
+        let src = "Event {{ event.name|quote }}\n\
+                   {% for att in atts %}\n\
+                   {{loop.index0}}: {{ att.name }}\n\
+                   {% endfor %}\n\
+                   {% for att in atts %}{% if not loop.first %}, {% endif %}{{att.name}}{% endfor %};\n\
+                   {% if codes %}\n\
+                   with codes\n\
+                   {% else %}\n\
+                   without codes {not a tag}\n\
+                   {% endif %}\n\
+                   {{ schema.database }}.{{ schema.evento }}.{{ schema.evento.nome }}\n";
+        let template = src.parse::<Template>().expect("valid template");
+        assert_eq!(
+            "Event 'Semana d''Ávila'\n0: A\n1: B\nA, B;\nwithout codes {not a tag}\n\
+            petcomp.evento.nome",
+            template.render(ctx()).expect("rendered template")
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for src in ["{{ name", "{% if codes %}", "{% endif %}"] {
+            assert!(src.parse::<Template>().is_err(), "{src}");
+        }
+
+        let render = |src: &str| src.parse::<Template>().unwrap().render(ctx());
+        for src in [
+            "{{ event.date }}",
+            "{{ schema.evento.titulo }}",
+            "{{ schema.eventos }}",
+        ] {
+            assert!(render(src).is_err(), "{src}");
+        }
+    }
+}